@@ -0,0 +1,188 @@
+use rand::rngs::StdRng;
+use rand::seq::IteratorRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::inventory::ItemDefinition;
+
+use super::AlchemyOperation;
+
+/// Makes up the item two others alchemize into. The same parents and operation always make the same item.
+pub fn generate_definition(
+	operation: AlchemyOperation,
+	first: &ItemDefinition,
+	second: &ItemDefinition,
+) -> ItemDefinition {
+	let code = operation.apply(first.code(), second.code());
+	let mut rng = StdRng::seed_from_u64(code.bits());
+
+	// The result takes its shape from one parent and its look from the other
+	let (base, other) = if rng.gen_bool(0.5) {
+		(first, second)
+	} else {
+		(second, first)
+	};
+
+	let definition = ItemDefinition {
+		name: String::new(),
+		code: Some(code),
+		mesh: base.mesh,
+		material: other.material.clone(),
+		icon: base.icon.clone(),
+		tags: Vec::new(),
+		size: base.size,
+		weight: base.weight,
+		weapon: base.weapon.clone(),
+		upgrade_material: base.upgrade_material,
+		consumable: base.consumable.clone(),
+		throw_damage: base.throw_damage.clone(),
+	};
+
+	match operation {
+		AlchemyOperation::And => ItemDefinition {
+			name: compound_name(base, other),
+			tags: tag_union(base, other),
+			size: base.size.max(other.size),
+			weight: base.weight + other.weight,
+			weapon: base.weapon.clone().or_else(|| other.weapon.clone()),
+			upgrade_material: base.upgrade_material || other.upgrade_material,
+			consumable: base.consumable.clone().or_else(|| other.consumable.clone()),
+			throw_damage: base
+				.throw_damage
+				.clone()
+				.or_else(|| other.throw_damage.clone()),
+			..definition
+		},
+		AlchemyOperation::Or => ItemDefinition {
+			name: portmanteau_name(base, other),
+			tags: tag_blend(base, other, &mut rng),
+			size: (base.size + other.size) * 0.5,
+			weight: (base.weight + other.weight) * 0.5,
+			upgrade_material: base.upgrade_material && other.upgrade_material,
+			..definition
+		},
+	}
+}
+
+/// Puts a word from `other` in front of `base`'s noun, like "Imp Sniper".
+fn compound_name(base: &ItemDefinition, other: &ItemDefinition) -> String {
+	let noun = last_word(&base.name);
+	let modifier = other
+		.name
+		.split_whitespace()
+		.find(|word| !base.name.contains(word))
+		.unwrap_or_else(|| last_word(&other.name));
+	format!("{modifier} {noun}")
+}
+
+/// Splices the front of `base`'s noun onto the back of `other`'s, like "Snirk".
+fn portmanteau_name(base: &ItemDefinition, other: &ItemDefinition) -> String {
+	let front = last_word(&base.name);
+	let back = last_word(&other.name);
+	let front_length = front.chars().count().div_ceil(2);
+	let back_length = back.chars().count() / 2;
+
+	let leading_words = base.name.trim_end().strip_suffix(front).unwrap_or_default();
+	let front: String = front.chars().take(front_length).collect();
+	let back: String = back.chars().skip(back_length).collect();
+	format!("{leading_words}{front}{back}")
+}
+
+fn last_word(name: &str) -> &str {
+	name.split_whitespace().next_back().unwrap_or(name)
+}
+
+/// Everything either parent is.
+fn tag_union(base: &ItemDefinition, other: &ItemDefinition) -> Vec<String> {
+	let mut tags = Vec::new();
+	for tag in base.tags.iter().chain(&other.tags) {
+		if !tags.contains(tag) {
+			tags.push(tag.clone());
+		}
+	}
+	tags
+}
+
+/// Whatever the parents have in common, plus a little of each.
+fn tag_blend(base: &ItemDefinition, other: &ItemDefinition, rng: &mut StdRng) -> Vec<String> {
+	let mut tags: Vec<String> = base
+		.tags
+		.iter()
+		.filter(|tag| other.tags.contains(tag))
+		.cloned()
+		.collect();
+	for parent in [base, other] {
+		let tag = parent
+			.tags
+			.iter()
+			.filter(|tag| !tags.contains(tag))
+			.choose(rng)
+			.cloned();
+		tags.extend(tag);
+	}
+	tags
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn item(ron: &str) -> ItemDefinition {
+		ron::from_str(ron).unwrap()
+	}
+
+	fn fork() -> ItemDefinition {
+		item(include_str!("../../assets/items/fork.item.ron"))
+	}
+
+	fn sniper() -> ItemDefinition {
+		item(include_str!("../../assets/items/sniper.item.ron"))
+	}
+
+	fn assert_same_definition(first: &ItemDefinition, second: &ItemDefinition) {
+		assert_eq!(first.name, second.name);
+		assert_eq!(first.code, second.code);
+		assert_eq!(first.mesh, second.mesh);
+		assert_eq!(first.material, second.material);
+		assert_eq!(first.icon, second.icon);
+		assert_eq!(first.tags, second.tags);
+		assert_eq!(first.size, second.size);
+		assert_eq!(first.weight, second.weight);
+		assert_eq!(first.weapon, second.weapon);
+		assert_eq!(first.upgrade_material, second.upgrade_material);
+	}
+
+	#[test]
+	fn alchemizing_the_same_items_always_makes_the_same_thing() {
+		let (fork, sniper) = (fork(), sniper());
+
+		for operation in [AlchemyOperation::And, AlchemyOperation::Or] {
+			let result = generate_definition(operation, &fork, &sniper);
+			for _ in 0..10 {
+				assert_same_definition(&result, &generate_definition(operation, &fork, &sniper));
+			}
+		}
+	}
+
+	#[test]
+	fn alchemized_items_get_the_combined_code() {
+		let (fork, sniper) = (fork(), sniper());
+
+		for operation in [AlchemyOperation::And, AlchemyOperation::Or] {
+			let result = generate_definition(operation, &fork, &sniper);
+			assert_eq!(
+				result.code,
+				Some(operation.apply(fork.code(), sniper.code()))
+			);
+		}
+	}
+
+	#[test]
+	fn and_keeps_every_tag_of_both_parents() {
+		let (fork, sniper) = (fork(), sniper());
+
+		let result = generate_definition(AlchemyOperation::And, &fork, &sniper);
+		for tag in fork.tags.iter().chain(&sniper.tags) {
+			assert!(result.tags.contains(tag), "{tag} went missing");
+		}
+	}
+}
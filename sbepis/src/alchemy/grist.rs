@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::camera::PlayerCameraNode;
+use crate::gravity::GravityRigidbodyBundle;
+use crate::inventory::{CaptchaCode, ItemDefinition};
+use crate::ok_or_return;
+use crate::player_controller::PlayerBody;
+
+/// How close the player has to get to grist to collect it
+const COLLECT_RADIUS: f32 = 1.5;
+const GRIST_SIZE: f32 = 0.08;
+/// Every item costs at least this much build grist
+const BASE_BUILD_COST: f32 = 2.0;
+/// How much build grist each unit of weight and size adds
+const WEIGHT_BUILD_COST: f32 = 4.0;
+const SIZE_BUILD_COST: f32 = 10.0;
+/// How much of a tag's grist each tag adds
+const TAG_COST: u32 = 3;
+
+/// What items are made of. Everything needs build grist, and the rest depends on what the item is like.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+pub enum GristType {
+	Build,
+	Shale,
+	Amber,
+	Chalk,
+	Iron,
+	Garnet,
+	Ruby,
+	Tar,
+}
+
+impl GristType {
+	pub const ALL: [Self; 8] = [
+		Self::Build,
+		Self::Shale,
+		Self::Amber,
+		Self::Chalk,
+		Self::Iron,
+		Self::Garnet,
+		Self::Ruby,
+		Self::Tar,
+	];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			GristType::Build => "Build",
+			GristType::Shale => "Shale",
+			GristType::Amber => "Amber",
+			GristType::Chalk => "Chalk",
+			GristType::Iron => "Iron",
+			GristType::Garnet => "Garnet",
+			GristType::Ruby => "Ruby",
+			GristType::Tar => "Tar",
+		}
+	}
+
+	pub fn color(self) -> Srgba {
+		match self {
+			GristType::Build => css::DEEP_SKY_BLUE,
+			GristType::Shale => css::MEDIUM_PURPLE,
+			GristType::Amber => css::ORANGE,
+			GristType::Chalk => css::WHITE_SMOKE,
+			GristType::Iron => css::SILVER,
+			GristType::Garnet => css::CRIMSON,
+			GristType::Ruby => css::DEEP_PINK,
+			GristType::Tar => css::DARK_SLATE_GRAY,
+		}
+	}
+
+	/// The grist an item tag calls for. Always the same for the same tag.
+	fn for_tag(tag: &str) -> Self {
+		let others = &Self::ALL[1..];
+		others[(CaptchaCode::from_name(tag).bits() % others.len() as u64) as usize]
+	}
+}
+
+/// An amount of each type of grist.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct GristAmounts(pub BTreeMap<GristType, u32>);
+
+impl GristAmounts {
+	pub fn add(&mut self, grist_type: GristType, amount: u32) {
+		*self.0.entry(grist_type).or_default() += amount;
+	}
+
+	pub fn get(&self, grist_type: GristType) -> u32 {
+		self.0.get(&grist_type).copied().unwrap_or_default()
+	}
+
+	pub fn covers(&self, cost: &GristAmounts) -> bool {
+		cost.0
+			.iter()
+			.all(|(&grist_type, &amount)| self.get(grist_type) >= amount)
+	}
+
+	/// Takes the cost out if there's enough of everything, and leaves it alone otherwise.
+	pub fn spend(&mut self, cost: &GristAmounts) -> bool {
+		if !self.covers(cost) {
+			return false;
+		}
+
+		for (grist_type, amount) in cost.0.iter() {
+			*self.0.entry(*grist_type).or_default() -= amount;
+		}
+		true
+	}
+}
+
+impl fmt::Display for GristAmounts {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut amounts = self.0.iter().filter(|(_, &amount)| amount > 0).peekable();
+		if amounts.peek().is_none() {
+			return write!(f, "Free");
+		}
+
+		for (index, (grist_type, amount)) in amounts.enumerate() {
+			if index > 0 {
+				write!(f, ", ")?;
+			}
+			write!(f, "{amount} {}", grist_type.name())?;
+		}
+		Ok(())
+	}
+}
+
+/// All the grist the player has collected.
+#[derive(Resource, Default)]
+pub struct GristCache(pub GristAmounts);
+
+/// What it costs to alchemize an item. Bigger and heavier things need more build grist, and each tag needs some of its own grist.
+pub fn grist_cost(definition: &ItemDefinition) -> GristAmounts {
+	let mut cost = GristAmounts::default();
+	let build =
+		BASE_BUILD_COST + definition.weight * WEIGHT_BUILD_COST + definition.size * SIZE_BUILD_COST;
+	cost.add(GristType::Build, build.ceil() as u32);
+	for tag in &definition.tags {
+		cost.add(GristType::for_tag(tag), TAG_COST);
+	}
+	cost
+}
+
+/// Grist lying around in the world, waiting for the player to walk over it.
+#[derive(Component)]
+pub struct GristPickup {
+	pub grist_type: GristType,
+	pub amount: u32,
+}
+
+/// The mesh and per-type materials every grist pickup shares.
+#[derive(Resource)]
+pub struct GristAssets {
+	mesh: Handle<Mesh>,
+	materials: BTreeMap<GristType, Handle<StandardMaterial>>,
+}
+
+pub fn load_grist_assets(
+	mut commands: Commands,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+	commands.insert_resource(GristAssets {
+		mesh: meshes.add(Sphere::new(GRIST_SIZE).mesh().ico(1).unwrap()),
+		materials: GristType::ALL
+			.into_iter()
+			.map(|grist_type| {
+				let color = grist_type.color();
+				let material = materials.add(StandardMaterial {
+					base_color: color.into(),
+					emissive: LinearRgba::from(color) * 2.0,
+					..default()
+				});
+				(grist_type, material)
+			})
+			.collect(),
+	});
+}
+
+pub fn spawn_grist(
+	commands: &mut Commands,
+	grist_assets: &GristAssets,
+	grist_type: GristType,
+	amount: u32,
+	position: Vec3,
+) -> Entity {
+	commands
+		.spawn((
+			Name::new(format!("{} Grist", grist_type.name())),
+			PbrBundle {
+				transform: Transform::from_translation(position),
+				mesh: grist_assets.mesh.clone(),
+				material: grist_assets.materials[&grist_type].clone(),
+				..default()
+			},
+			GravityRigidbodyBundle::default(),
+			Collider::ball(GRIST_SIZE),
+			GristPickup { grist_type, amount },
+		))
+		.id()
+}
+
+pub fn collect_grist(
+	mut commands: Commands,
+	pickups: Query<(Entity, &GlobalTransform, &GristPickup)>,
+	player: Query<&GlobalTransform, With<PlayerBody>>,
+	mut cache: ResMut<GristCache>,
+) {
+	let player = ok_or_return!(player.get_single());
+	for (entity, transform, pickup) in pickups.iter() {
+		if transform.translation().distance(player.translation()) > COLLECT_RADIUS {
+			continue;
+		}
+
+		cache.0.add(pickup.grist_type, pickup.amount);
+		commands.entity(entity).despawn_recursive();
+	}
+}
+
+#[derive(Component)]
+pub struct GristHud;
+
+pub fn spawn_grist_hud(mut commands: Commands) {
+	commands.spawn((
+		Name::new("Grist HUD"),
+		TextBundle::default().with_style(Style {
+			position_type: PositionType::Absolute,
+			top: Val::Px(5.0),
+			right: Val::Px(5.0),
+			..default()
+		}),
+		PlayerCameraNode,
+		GristHud,
+	));
+}
+
+pub fn update_grist_hud(cache: Res<GristCache>, mut huds: Query<&mut Text, With<GristHud>>) {
+	for mut text in huds.iter_mut() {
+		text.sections = GristType::ALL
+			.iter()
+			.map(|&grist_type| {
+				TextSection::new(
+					format!("{} {}\n", grist_type.name(), cache.0.get(grist_type)),
+					TextStyle {
+						color: grist_type.color().into(),
+						..default()
+					},
+				)
+			})
+			.collect();
+	}
+}
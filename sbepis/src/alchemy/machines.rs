@@ -0,0 +1,463 @@
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::InputMap;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::PlayerCameraNode;
+use crate::input::input_manager;
+use crate::inventory::{spawn_item, Inventory, Item, ItemCatalog, ItemDefinition};
+use crate::menus::*;
+use crate::player_controller::{Interactable, PlayerBody};
+use crate::save::SaveData;
+use crate::{gridbox_material, ok_or_continue, ok_or_return, some_or_continue};
+
+use super::grist::{grist_cost, GristCache};
+use super::{preview_alchemy, Alchemize, AlchemyOperation, Recipe};
+
+const MACHINE_SIZE: f32 = 1.5;
+/// Where the row of machines starts, and how far apart they are
+const MACHINES_POSITION: Vec3 = Vec3::new(-6.0, MACHINE_SIZE * 0.5, -8.0);
+const MACHINE_SPACING: f32 = 4.0;
+const DOWEL_PATH: &str = "items/dowel.item.ron";
+const PUNCHED_CARD_PATH: &str = "items/punched_card.item.ron";
+const TOTEM_PATH: &str = "items/totem.item.ron";
+/// How many punched cards the lathe can stack at once
+const LATHE_CARDS: usize = 2;
+
+/// One step of the alchemy pipeline: dowels from the cruxtruder and cards from the designix get carved into totems, which the alchemiter makes real.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub enum AlchemyMachine {
+	Cruxtruder,
+	/// Punching a code onto a card that's already punched combines them with `||`
+	PunchDesignix {
+		card: Option<Recipe>,
+	},
+	/// Stacked cards combine with `&&`
+	TotemLathe {
+		cards: Vec<Recipe>,
+		dowel: bool,
+	},
+	Alchemiter,
+}
+
+impl AlchemyMachine {
+	/// Every machine, in the order they're set up, before anything's been done with them.
+	fn pipeline() -> [Self; 4] {
+		[
+			AlchemyMachine::Cruxtruder,
+			AlchemyMachine::PunchDesignix { card: None },
+			AlchemyMachine::TotemLathe {
+				cards: Vec::new(),
+				dowel: false,
+			},
+			AlchemyMachine::Alchemiter,
+		]
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			AlchemyMachine::Cruxtruder => "Cruxtruder",
+			AlchemyMachine::PunchDesignix { .. } => "Punch Designix",
+			AlchemyMachine::TotemLathe { .. } => "Totem Lathe",
+			AlchemyMachine::Alchemiter => "Alchemiter",
+		}
+	}
+
+	fn material(&self) -> &'static str {
+		match self {
+			AlchemyMachine::Cruxtruder => "cyan",
+			AlchemyMachine::PunchDesignix { .. } => "white",
+			AlchemyMachine::TotemLathe { .. } => "grey4",
+			AlchemyMachine::Alchemiter => "blue1",
+		}
+	}
+}
+
+/// Comes out of the cruxtruder, and gets carved into a totem.
+#[derive(Component)]
+pub struct CruxiteDowel;
+
+#[derive(Component)]
+pub struct PunchedCard(pub Recipe);
+
+#[derive(Component)]
+pub struct Totem(pub Recipe);
+
+/// The menu for using one machine. Closes when it's done with.
+#[derive(Component)]
+pub struct MachineMenu {
+	pub machine: Entity,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum MachineAction {
+	ExtrudeDowel,
+	/// Punch an inventory item's code onto the designix's card
+	Punch(Entity),
+	EjectCard,
+	/// Put a punched card or dowel from the inventory into the lathe
+	Insert(Entity),
+	Carve,
+	/// Make the item on a totem from the inventory
+	Alchemize(Entity),
+}
+
+#[derive(Component)]
+pub struct MachineButton {
+	pub machine: Entity,
+	pub action: MachineAction,
+}
+
+pub fn spawn_alchemy_machines(
+	mut commands: Commands,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+	asset_server: Res<AssetServer>,
+	save_data: Res<SaveData>,
+) {
+	let mesh = meshes.add(Cuboid::from_size(Vec3::splat(MACHINE_SIZE)));
+	for (index, machine) in AlchemyMachine::pipeline().into_iter().enumerate() {
+		let machine = save_data
+			.alchemy_machines
+			.get(machine.name())
+			.cloned()
+			.unwrap_or(machine);
+		commands.spawn((
+			Name::new(machine.name()),
+			PbrBundle {
+				transform: Transform::from_translation(
+					MACHINES_POSITION + Vec3::X * MACHINE_SPACING * index as f32,
+				),
+				mesh: mesh.clone(),
+				material: gridbox_material(machine.material(), &mut materials, &asset_server),
+				..default()
+			},
+			RigidBody::Fixed,
+			Interactable::new("Use").with_priority(1),
+			Collider::cuboid(MACHINE_SIZE * 0.5, MACHINE_SIZE * 0.5, MACHINE_SIZE * 0.5),
+			machine,
+		));
+	}
+}
+
+pub fn open_machine_menu(
+	In(machine): In<Entity>,
+	mut commands: Commands,
+	machines: Query<&AlchemyMachine>,
+	mut menu_stack: ResMut<MenuStack>,
+) {
+	let name = ok_or_return!(machines.get(machine)).name();
+	let menu = commands
+		.spawn((
+			Name::new(format!("{name} Menu")),
+			NodeBundle {
+				style: Style {
+					margin: UiRect::all(Val::Auto),
+					width: Val::Percent(100.0),
+					max_width: Val::Px(600.0),
+					padding: UiRect::all(Val::Px(10.0)),
+					row_gap: Val::Px(10.0),
+					flex_direction: FlexDirection::Column,
+					..default()
+				},
+				background_color: css::GRAY.into(),
+				..default()
+			},
+			PlayerCameraNode,
+			input_manager(
+				InputMap::default().with(MenuAction::CloseMenu, KeyCode::Tab),
+				false,
+			),
+			Menu,
+			MenuWithMouse,
+			MenuWithInputManager,
+			MenuDespawnsWhenClosed,
+			MachineMenu { machine },
+		))
+		.id();
+	menu_stack.push(menu);
+}
+
+/// Rebuilds machine menus whenever what they show might have changed.
+pub fn update_machine_menus(
+	mut commands: Commands,
+	menus: Query<(Entity, Ref<MachineMenu>)>,
+	machines: Query<Ref<AlchemyMachine>>,
+	inventories: Query<Ref<Inventory>, With<PlayerBody>>,
+	names: Query<&Name>,
+	punched_cards: Query<(), With<PunchedCard>>,
+	dowels: Query<(), With<CruxiteDowel>>,
+	totems: Query<&Totem>,
+	catalog: Res<ItemCatalog>,
+	definitions: Res<Assets<ItemDefinition>>,
+	cache: Res<GristCache>,
+) {
+	let inventory = ok_or_return!(inventories.get_single());
+	for (menu_entity, menu) in menus.iter() {
+		let machine = ok_or_continue!(machines.get(menu.machine));
+		if !menu.is_added()
+			&& !machine.is_changed()
+			&& !inventory.is_changed()
+			&& !cache.is_changed()
+		{
+			continue;
+		}
+
+		let name_of = |item: Entity| names.get(item).map_or("???", |name| name.as_str());
+		let mut buttons: Vec<(MachineAction, String, Srgba)> = Vec::new();
+		let status = match &*machine {
+			AlchemyMachine::Cruxtruder => {
+				buttons.push((
+					MachineAction::ExtrudeDowel,
+					"Extrude dowel".to_owned(),
+					css::DARK_GRAY,
+				));
+				"Makes cruxite dowels for the totem lathe.".to_owned()
+			}
+			AlchemyMachine::PunchDesignix { card } => {
+				for item in inventory.items() {
+					buttons.push((
+						MachineAction::Punch(item),
+						format!("Punch {}", name_of(item)),
+						css::DARK_GRAY,
+					));
+				}
+				match card {
+					Some(card) => {
+						buttons.push((
+							MachineAction::EjectCard,
+							"Eject card".to_owned(),
+							css::DARK_GRAY,
+						));
+						format!(
+							"Card punched with {}. Punching again adds holes with ||.",
+							card.code()
+						)
+					}
+					None => "Card is blank. Punch an item's code to start.".to_owned(),
+				}
+			}
+			AlchemyMachine::TotemLathe { cards, dowel } => {
+				for item in inventory.items() {
+					let fits = (punched_cards.contains(item) && cards.len() < LATHE_CARDS)
+						|| (dowels.contains(item) && !dowel);
+					if fits {
+						buttons.push((
+							MachineAction::Insert(item),
+							format!("Insert {}", name_of(item)),
+							css::DARK_GRAY,
+						));
+					}
+				}
+				if *dowel && !cards.is_empty() {
+					buttons.push((
+						MachineAction::Carve,
+						"Carve totem".to_owned(),
+						css::DARK_GRAY,
+					));
+				}
+				let codes: Vec<String> = cards.iter().map(|card| card.code().to_string()).collect();
+				format!(
+					"Cards: {}. Stacked cards combine with &&.\nDowel: {}",
+					if codes.is_empty() {
+						"none".to_owned()
+					} else {
+						codes.join(", ")
+					},
+					if *dowel { "loaded" } else { "none" },
+				)
+			}
+			AlchemyMachine::Alchemiter => {
+				for item in inventory.items() {
+					let Totem(recipe) = ok_or_continue!(totems.get(item));
+					let Some(result) = preview_alchemy(recipe, &catalog, &definitions) else {
+						continue;
+					};
+					let cost = grist_cost(&result);
+					let color = if cache.0.covers(&cost) {
+						css::DARK_GRAY
+					} else {
+						css::DARK_RED
+					};
+					buttons.push((
+						MachineAction::Alchemize(item),
+						format!("{} ({cost})", result.name),
+						color,
+					));
+				}
+				"Makes whatever's on a totem, for grist.".to_owned()
+			}
+		};
+
+		commands
+			.entity(menu_entity)
+			.despawn_descendants()
+			.with_children(|parent| {
+				parent.spawn(TextBundle::from_section(
+					format!("{}\n\n{status}\n\n[Tab] to close", machine.name()),
+					TextStyle {
+						font_size: 20.0,
+						color: Color::WHITE,
+						..default()
+					},
+				));
+				for (action, label, color) in buttons {
+					parent
+						.spawn((
+							ButtonBundle {
+								style: Style {
+									padding: UiRect::all(Val::Px(10.0)),
+									..default()
+								},
+								background_color: color.into(),
+								..default()
+							},
+							MachineButton {
+								machine: menu.machine,
+								action,
+							},
+						))
+						.with_children(|parent| {
+							parent.spawn(TextBundle::from_section(label, TextStyle::default()));
+						});
+				}
+			});
+	}
+}
+
+pub fn press_machine_buttons(
+	mut commands: Commands,
+	buttons: Query<(&MachineButton, &Interaction), Changed<Interaction>>,
+	mut machines: Query<(&mut AlchemyMachine, &GlobalTransform)>,
+	mut inventories: Query<&mut Inventory, With<PlayerBody>>,
+	items: Query<&Item>,
+	punched_cards: Query<&PunchedCard>,
+	dowels: Query<(), With<CruxiteDowel>>,
+	totems: Query<&Totem>,
+	definitions: Res<Assets<ItemDefinition>>,
+	asset_server: Res<AssetServer>,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+	mut ev_alchemize: EventWriter<Alchemize>,
+) {
+	let mut inventory = ok_or_return!(inventories.get_single_mut());
+	for (button, interaction) in buttons.iter() {
+		if *interaction != Interaction::Pressed {
+			continue;
+		}
+		let (mut machine, transform) = ok_or_continue!(machines.get_mut(button.machine));
+		// Whatever the machine makes gets put on top of it
+		let output = transform.translation() + transform.up() * MACHINE_SIZE;
+		let mut spawn_output = |commands: &mut Commands, path: &str| {
+			let handle = asset_server.load(path.to_owned());
+			let definition = definitions.get(&handle)?;
+			Some(spawn_item(
+				commands,
+				&asset_server,
+				&mut meshes,
+				&mut materials,
+				definition,
+				handle,
+				output,
+			))
+		};
+
+		// Presses that don't change anything shouldn't make the machine look changed and rewrite the save
+		let changed = match (button.action, machine.bypass_change_detection()) {
+			(MachineAction::ExtrudeDowel, AlchemyMachine::Cruxtruder) => {
+				if let Some(dowel) = spawn_output(&mut commands, DOWEL_PATH) {
+					commands.entity(dowel).insert(CruxiteDowel);
+				}
+				false
+			}
+			(MachineAction::Punch(item), AlchemyMachine::PunchDesignix { card }) => {
+				if !inventory.items().any(|other| other == item) {
+					continue;
+				}
+				let code = Recipe::Code(ok_or_continue!(items.get(item)).code);
+				*card = Some(match card.take() {
+					Some(card) => card.combine(AlchemyOperation::Or, code),
+					None => code,
+				});
+				true
+			}
+			(MachineAction::EjectCard, AlchemyMachine::PunchDesignix { card }) => {
+				let recipe = some_or_continue!(card.clone());
+				let punched_card =
+					some_or_continue!(spawn_output(&mut commands, PUNCHED_CARD_PATH));
+				*card = None;
+				commands.entity(punched_card).insert((
+					Name::new(format!("Punched Card {}", recipe.code())),
+					PunchedCard(recipe),
+				));
+				true
+			}
+			(MachineAction::Insert(item), AlchemyMachine::TotemLathe { cards, dowel }) => {
+				if !inventory.items().any(|other| other == item) {
+					continue;
+				}
+				if let Ok(PunchedCard(recipe)) = punched_cards.get(item) {
+					if cards.len() >= LATHE_CARDS {
+						continue;
+					}
+					cards.push(recipe.clone());
+				} else if dowels.contains(item) && !*dowel {
+					*dowel = true;
+				} else {
+					continue;
+				}
+				inventory.take(item);
+				commands.entity(item).despawn_recursive();
+				true
+			}
+			(MachineAction::Carve, AlchemyMachine::TotemLathe { cards, dowel }) => {
+				if !*dowel || cards.is_empty() {
+					continue;
+				}
+				let totem = some_or_continue!(spawn_output(&mut commands, TOTEM_PATH));
+				let mut stack = cards.drain(..);
+				let first = stack
+					.next()
+					.expect("Lathe has no cards even though we checked");
+				let recipe = stack.fold(first, |recipe, card| {
+					recipe.combine(AlchemyOperation::And, card)
+				});
+				*dowel = false;
+				commands
+					.entity(totem)
+					.insert((Name::new(format!("Totem {}", recipe.code())), Totem(recipe)));
+				true
+			}
+			(MachineAction::Alchemize(item), AlchemyMachine::Alchemiter) => {
+				if !inventory.items().any(|other| other == item) {
+					continue;
+				}
+				let Totem(recipe) = ok_or_continue!(totems.get(item));
+				ev_alchemize.send(Alchemize {
+					recipe: recipe.clone(),
+					position: output,
+				});
+				false
+			}
+			_ => false,
+		};
+		if changed {
+			machine.set_changed();
+		}
+	}
+}
+
+/// Keeps the save up to date with what's loaded into each machine.
+pub fn save_alchemy_machines(
+	machines: Query<Ref<AlchemyMachine>>,
+	mut save_data: ResMut<SaveData>,
+) {
+	for machine in machines.iter() {
+		if machine.is_changed() && !machine.is_added() {
+			save_data
+				.alchemy_machines
+				.insert(machine.name().to_owned(), (*machine).clone());
+		}
+	}
+}
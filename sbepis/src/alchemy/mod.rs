@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::inventory::{spawn_item, CaptchaCode, ItemCatalog, ItemDefinition};
+use crate::iter_system::*;
+use crate::player_controller::interact_with;
+use crate::some_or_continue;
+
+use self::generator::generate_definition;
+use self::grist::*;
+pub use self::grist::{grist_cost, spawn_grist, GristAmounts, GristAssets, GristCache};
+pub use self::machines::AlchemyMachine;
+use self::machines::*;
+
+mod generator;
+mod grist;
+mod machines;
+
+pub struct AlchemyPlugin;
+impl Plugin for AlchemyPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_event::<Alchemize>()
+			.init_resource::<GristCache>()
+			.add_systems(
+				Startup,
+				(spawn_grist_hud, load_grist_assets, spawn_alchemy_machines),
+			)
+			.add_systems(
+				Update,
+				(
+					alchemize,
+					collect_grist,
+					update_grist_hud.run_if(resource_changed::<GristCache>),
+					interact_with::<AlchemyMachine>
+						.iter_filter_some()
+						.iter_do(open_machine_menu)
+						.iter_done(),
+					update_machine_menus,
+					press_machine_buttons,
+					save_alchemy_machines,
+				),
+			);
+	}
+}
+
+/// The two ways captcha codes can be combined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AlchemyOperation {
+	/// `&&`, which tends to make something that does what both parents do
+	And,
+	/// `||`, which tends to make something halfway between its parents
+	Or,
+}
+
+impl AlchemyOperation {
+	pub fn apply(self, first: CaptchaCode, second: CaptchaCode) -> CaptchaCode {
+		match self {
+			AlchemyOperation::And => first & second,
+			AlchemyOperation::Or => first | second,
+		}
+	}
+}
+
+/// How a captcha code was put together, which alchemy needs to know to work out what the code makes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Recipe {
+	/// The code of an item that already exists
+	Code(CaptchaCode),
+	Combine(AlchemyOperation, Box<Recipe>, Box<Recipe>),
+}
+
+impl Recipe {
+	pub fn combine(self, operation: AlchemyOperation, other: Recipe) -> Self {
+		Recipe::Combine(operation, Box::new(self), Box::new(other))
+	}
+
+	pub fn code(&self) -> CaptchaCode {
+		match self {
+			Recipe::Code(code) => *code,
+			Recipe::Combine(operation, first, second) => {
+				operation.apply(first.code(), second.code())
+			}
+		}
+	}
+}
+
+/// Asks for the item a recipe makes to be put into the world, if there's enough grist for it.
+#[derive(Event)]
+pub struct Alchemize {
+	pub recipe: Recipe,
+	pub position: Vec3,
+}
+
+/// What a recipe would make, whether anyone's made it before or not. `None` if any code in it isn't a known item.
+pub fn preview_alchemy(
+	recipe: &Recipe,
+	catalog: &ItemCatalog,
+	definitions: &Assets<ItemDefinition>,
+) -> Option<ItemDefinition> {
+	if let Some(existing) = catalog
+		.get(recipe.code())
+		.and_then(|handle| definitions.get(handle))
+	{
+		return Some(existing.clone());
+	}
+
+	let Recipe::Combine(operation, first, second) = recipe else {
+		return None;
+	};
+	let first = preview_alchemy(first, catalog, definitions)?;
+	let second = preview_alchemy(second, catalog, definitions)?;
+	Some(generate_definition(*operation, &first, &second))
+}
+
+/// Spawns the results of alchemy for grist, making up definitions for any codes nobody has made before.
+fn alchemize(
+	mut commands: Commands,
+	mut ev_alchemize: EventReader<Alchemize>,
+	mut catalog: ResMut<ItemCatalog>,
+	mut definitions: ResMut<Assets<ItemDefinition>>,
+	mut cache: ResMut<GristCache>,
+	asset_server: Res<AssetServer>,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+	for ev in ev_alchemize.read() {
+		let definition = some_or_continue!(preview_alchemy(&ev.recipe, &catalog, &definitions));
+		if !cache.0.spend(&grist_cost(&definition)) {
+			info!("Not enough grist to alchemize {}", definition.name);
+			continue;
+		}
+
+		let code = definition.code();
+		let handle = if let Some(handle) = catalog.get(code).cloned() {
+			handle
+		} else {
+			info!("Alchemized {} for the first time", definition.name);
+			let handle = definitions.add(definition.clone());
+			catalog.insert(code, handle.clone());
+			handle
+		};
+
+		spawn_item(
+			&mut commands,
+			&asset_server,
+			&mut meshes,
+			&mut materials,
+			&definition,
+			handle,
+			ev.position,
+		);
+	}
+}
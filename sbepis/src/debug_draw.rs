@@ -0,0 +1,123 @@
+use bevy::color::palettes::css;
+use bevy::input::common_conditions::input_just_pressed;
+use bevy::prelude::*;
+
+use crate::entity::spawner::Spawner;
+use crate::gravity::AffectedByGravity;
+
+/// How long gravity arrows are per unit of acceleration
+const GRAVITY_ARROW_SCALE: f32 = 0.1;
+/// How long each axis of a spawner's marker is
+const SPAWNER_MARKER_LENGTH: f32 = 0.5;
+
+pub struct DebugDrawPlugin;
+impl Plugin for DebugDrawPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<DebugDraw>().add_systems(
+			Update,
+			(
+				toggle_debug_draw.run_if(input_just_pressed(KeyCode::F3)),
+				draw_debug_shapes,
+				draw_gravity,
+				draw_spawners,
+			),
+		);
+	}
+}
+
+pub enum DebugShape {
+	Line {
+		start: Vec3,
+		end: Vec3,
+	},
+	/// A unit cube scaled, rotated and moved by the transform
+	Cuboid(Transform),
+}
+
+struct TimedShape {
+	shape: DebugShape,
+	color: Color,
+	/// Seconds left to draw it for
+	remaining: f32,
+}
+
+/// A gizmo overlay for seeing what gameplay systems are doing, toggled with F3.
+/// Systems hand it shapes to keep drawing for a while, since most of what's worth seeing only lasts a frame.
+#[derive(Resource, Default)]
+pub struct DebugDraw {
+	pub enabled: bool,
+	shapes: Vec<TimedShape>,
+}
+
+impl DebugDraw {
+	/// Draws the shape for the given number of seconds. Does nothing while the overlay is off.
+	pub fn draw(&mut self, shape: DebugShape, color: impl Into<Color>, seconds: f32) {
+		if !self.enabled {
+			return;
+		}
+
+		self.shapes.push(TimedShape {
+			shape,
+			color: color.into(),
+			remaining: seconds,
+		});
+	}
+}
+
+fn toggle_debug_draw(mut debug_draw: ResMut<DebugDraw>) {
+	debug_draw.enabled = !debug_draw.enabled;
+	debug_draw.shapes.clear();
+}
+
+fn draw_debug_shapes(mut debug_draw: ResMut<DebugDraw>, mut gizmos: Gizmos, time: Res<Time>) {
+	for timed in debug_draw.shapes.iter_mut() {
+		match timed.shape {
+			DebugShape::Line { start, end } => gizmos.line(start, end, timed.color),
+			DebugShape::Cuboid(transform) => gizmos.cuboid(transform, timed.color),
+		}
+		timed.remaining -= time.delta_seconds();
+	}
+
+	debug_draw.shapes.retain(|timed| timed.remaining > 0.0);
+}
+
+fn draw_gravity(
+	debug_draw: Res<DebugDraw>,
+	mut gizmos: Gizmos,
+	bodies: Query<(&GlobalTransform, &AffectedByGravity)>,
+) {
+	if !debug_draw.enabled {
+		return;
+	}
+
+	for (transform, gravity) in bodies.iter() {
+		let position = transform.translation();
+		gizmos.arrow(
+			position,
+			position + gravity.acceleration * GRAVITY_ARROW_SCALE,
+			css::PURPLE,
+		);
+	}
+}
+
+/// Spawners put everything at a single point, so they're drawn as that point's axes.
+fn draw_spawners(
+	debug_draw: Res<DebugDraw>,
+	mut gizmos: Gizmos,
+	spawners: Query<&GlobalTransform, With<Spawner>>,
+) {
+	if !debug_draw.enabled {
+		return;
+	}
+
+	for transform in spawners.iter() {
+		let position = transform.translation();
+		for (axis, color) in [
+			(transform.right(), css::RED),
+			(transform.up(), css::LIME),
+			(transform.back(), css::BLUE),
+		] {
+			gizmos.line(position, position + axis * SPAWNER_MARKER_LENGTH, color);
+		}
+	}
+}
@@ -0,0 +1,421 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::fray::{ChartEventFired, ChartEventKind, FrayMusic};
+use crate::player_controller::{Dead, PlayerBody};
+use crate::{gridbox_material_extra, ok_or_return};
+
+use super::health::DamageType;
+use super::status::StatusEffects;
+use super::EntityDamaged;
+
+const HAZARD_RADIUS: f32 = 2.0;
+const HAZARD_DAMAGE: f32 = 1.0;
+const HAZARD_THICKNESS: f32 = 0.05;
+
+/// Attacks the player in time with the Fray: telegraphs on one beat and strikes on the next.
+#[derive(Component)]
+pub struct BeatAttacker {
+	pub damage: f32,
+	pub range: f32,
+	/// How many ticks per beat this attacker can start an attack on
+	pub subdivisions: u32,
+	/// How many beats to wait after a strike before telegraphing again
+	pub cooldown: u32,
+	state: BeatAttackState,
+}
+
+impl BeatAttacker {
+	pub fn new(damage: f32, range: f32, subdivisions: u32, cooldown: u32) -> Self {
+		Self {
+			damage,
+			range,
+			subdivisions,
+			cooldown,
+			state: BeatAttackState::Idle { ready_beat: 0.0 },
+		}
+	}
+
+	/// The warning for the attack this attacker is winding up, if any.
+	pub fn telegraph(&self) -> Option<Entity> {
+		match self.state {
+			BeatAttackState::Idle { .. } => None,
+			BeatAttackState::Telegraphing { telegraph, .. } => Some(telegraph),
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+enum BeatAttackState {
+	Idle {
+		ready_beat: f64,
+	},
+	Telegraphing {
+		telegraph_beat: f64,
+		strike_beat: f64,
+		telegraph: Entity,
+	},
+}
+
+/// Makes a `BeatAttacker` wait for attacks from the active track's chart instead of picking its own timings.
+#[derive(Component)]
+pub struct ChartedAttacker {
+	pub lane: u8,
+	/// How close the player has to be for this attacker to join in on a charted attack
+	pub engage_range: f32,
+}
+
+/// Strikes that land while this is active don't deal damage.
+#[derive(Component)]
+pub struct Evading {
+	pub evasion: Evasion,
+	/// How many beats the evasion protects for
+	pub window: f32,
+	/// How many beats after the window before evading again
+	pub cooldown: f32,
+	pub time: Duration,
+}
+
+impl Evading {
+	pub fn new(evasion: Evasion, window: f32, cooldown: f32) -> Self {
+		Self {
+			evasion,
+			window,
+			cooldown,
+			time: Duration::ZERO,
+		}
+	}
+
+	pub fn is_active(&self, fray: &FrayMusic) -> bool {
+		fray.time_to_bpm_beat(self.time) as f32 <= self.window
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Evasion {
+	Dodge,
+	Parry,
+}
+
+#[derive(Event)]
+pub struct StrikeEvaded {
+	pub attacker: Entity,
+	pub evasion: Evasion,
+}
+
+/// A patch of ground that hurts whoever's standing on it when it erupts.
+#[derive(Component)]
+pub struct ArenaHazard {
+	pub damage: f32,
+	pub radius: f32,
+	pub warn_beat: f64,
+	pub erupt_beat: f64,
+}
+
+#[derive(Resource)]
+pub struct TelegraphAssets {
+	mesh: Handle<Mesh>,
+	material: Handle<StandardMaterial>,
+}
+
+pub fn load_telegraph_assets(
+	mut commands: Commands,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+	asset_server: Res<AssetServer>,
+) {
+	commands.insert_resource(TelegraphAssets {
+		mesh: meshes.add(Sphere::new(1.0)),
+		material: gridbox_material_extra(
+			"red",
+			&mut materials,
+			&asset_server,
+			StandardMaterial {
+				base_color: Color::srgba(1.0, 1.0, 1.0, 0.3),
+				alpha_mode: AlphaMode::Blend,
+				..default()
+			},
+		),
+	});
+}
+
+fn start_telegraph(
+	commands: &mut Commands,
+	telegraph_assets: &TelegraphAssets,
+	attacker_entity: Entity,
+	telegraph_beat: f64,
+	strike_beat: f64,
+) -> BeatAttackState {
+	let telegraph = commands
+		.spawn((
+			Name::new("Attack Telegraph"),
+			PbrBundle {
+				transform: Transform::from_scale(Vec3::ZERO),
+				mesh: telegraph_assets.mesh.clone(),
+				material: telegraph_assets.material.clone(),
+				..default()
+			},
+		))
+		.set_parent(attacker_entity)
+		.id();
+
+	BeatAttackState::Telegraphing {
+		telegraph_beat,
+		strike_beat,
+		telegraph,
+	}
+}
+
+pub fn beat_attack(
+	mut commands: Commands,
+	mut attackers: Query<(
+		Entity,
+		&mut BeatAttacker,
+		&GlobalTransform,
+		Has<ChartedAttacker>,
+		Option<&StatusEffects>,
+	)>,
+	player: Query<(Entity, &GlobalTransform, Option<&Evading>), (With<PlayerBody>, Without<Dead>)>,
+	mut telegraph_transforms: Query<&mut Transform>,
+	fray: Query<&FrayMusic>,
+	telegraph_assets: Res<TelegraphAssets>,
+	mut ev_hit: EventWriter<EntityDamaged>,
+	mut ev_evaded: EventWriter<StrikeEvaded>,
+	asset_server: Res<AssetServer>,
+) {
+	let fray = fray.get_single().expect("Could not find fray");
+	let (player, player_transform, evading) = ok_or_return!(player.get_single());
+	let beat = fray.beat();
+
+	for (attacker_entity, mut attacker, transform, charted, statuses) in attackers.iter_mut() {
+		let stunned = statuses.is_some_and(StatusEffects::is_stunned);
+		let in_range = transform
+			.translation()
+			.distance(player_transform.translation())
+			<= attacker.range;
+
+		let state = attacker.state;
+		match state {
+			BeatAttackState::Idle { ready_beat } => {
+				if stunned || charted || beat < ready_beat || !in_range {
+					continue;
+				}
+
+				let subdivisions = attacker.subdivisions as f64;
+				let telegraph_beat = fray.subbeats(attacker.subdivisions) as f64 / subdivisions;
+				attacker.state = start_telegraph(
+					&mut commands,
+					&telegraph_assets,
+					attacker_entity,
+					telegraph_beat,
+					telegraph_beat + 1.0,
+				);
+			}
+			BeatAttackState::Telegraphing {
+				telegraph_beat,
+				strike_beat,
+				telegraph,
+			} => {
+				// Getting stunned mid-telegraph calls the attack off
+				if stunned {
+					commands.entity(telegraph).despawn_recursive();
+					attacker.state = BeatAttackState::Idle { ready_beat: beat };
+					continue;
+				}
+
+				if beat < strike_beat {
+					let progress =
+						((beat - telegraph_beat) / (strike_beat - telegraph_beat)) as f32;
+					if let Ok(mut telegraph_transform) = telegraph_transforms.get_mut(telegraph) {
+						telegraph_transform.scale =
+							Vec3::splat(progress.clamp(0.0, 1.0) * attacker.range);
+					}
+					continue;
+				}
+
+				commands.entity(telegraph).despawn_recursive();
+
+				commands.spawn((
+					Name::new("Attack Strike SFX"),
+					AudioBundle {
+						source: asset_server.load("whoosh.mp3"),
+						settings: PlaybackSettings::DESPAWN,
+					},
+				));
+
+				let mut cooldown = attacker.cooldown as f64;
+				match evading.filter(|evading| evading.is_active(fray)) {
+					Some(evading) if in_range => {
+						ev_evaded.send(StrikeEvaded {
+							attacker: attacker_entity,
+							evasion: evading.evasion,
+						});
+
+						// Getting parried throws the attacker off for a while
+						if let Evasion::Parry = evading.evasion {
+							cooldown *= 2.0;
+						}
+					}
+					None if in_range => {
+						ev_hit.send(EntityDamaged {
+							victim: player,
+							damage: attacker.damage,
+							fray_modifier: 1.0,
+							damage_type: DamageType::Blunt,
+							effects: Vec::new(),
+							knockback: Vec3::ZERO,
+						});
+					}
+					_ => {}
+				}
+
+				attacker.state = BeatAttackState::Idle {
+					ready_beat: strike_beat + cooldown,
+				};
+			}
+		}
+	}
+}
+
+pub fn chart_attack(
+	mut commands: Commands,
+	mut ev_chart: EventReader<ChartEventFired>,
+	mut attackers: Query<(
+		Entity,
+		&mut BeatAttacker,
+		&ChartedAttacker,
+		&GlobalTransform,
+		Option<&StatusEffects>,
+	)>,
+	player: Query<&GlobalTransform, (With<PlayerBody>, Without<Dead>)>,
+	telegraph_assets: Res<TelegraphAssets>,
+) {
+	let player_transform = ok_or_return!(player.get_single());
+
+	for ChartEventFired(event) in ev_chart.read() {
+		if event.kind != ChartEventKind::Attack {
+			continue;
+		}
+
+		for (attacker_entity, mut attacker, charted, transform, statuses) in attackers.iter_mut() {
+			let engaged = transform
+				.translation()
+				.distance(player_transform.translation())
+				<= charted.engage_range;
+			if charted.lane != event.lane
+				|| !engaged || statuses.is_some_and(StatusEffects::is_stunned)
+				|| !matches!(attacker.state, BeatAttackState::Idle { .. })
+			{
+				continue;
+			}
+
+			attacker.state = start_telegraph(
+				&mut commands,
+				&telegraph_assets,
+				attacker_entity,
+				event.beat,
+				event.beat + event.duration,
+			);
+		}
+	}
+}
+
+pub fn spawn_chart_hazards(
+	mut commands: Commands,
+	mut ev_chart: EventReader<ChartEventFired>,
+	player: Query<&Transform, (With<PlayerBody>, Without<Dead>)>,
+	telegraph_assets: Res<TelegraphAssets>,
+) {
+	let player_transform = ok_or_return!(player.get_single());
+
+	for ChartEventFired(event) in ev_chart.read() {
+		if event.kind != ChartEventKind::Hazard {
+			continue;
+		}
+
+		commands.spawn((
+			Name::new("Arena Hazard"),
+			PbrBundle {
+				transform: Transform::from_translation(player_transform.translation)
+					.with_rotation(player_transform.rotation)
+					.with_scale(Vec3::ZERO),
+				mesh: telegraph_assets.mesh.clone(),
+				material: telegraph_assets.material.clone(),
+				..default()
+			},
+			ArenaHazard {
+				damage: HAZARD_DAMAGE * event.intensity,
+				radius: HAZARD_RADIUS,
+				warn_beat: event.beat,
+				erupt_beat: event.beat + event.duration,
+			},
+		));
+	}
+}
+
+pub fn erupt_hazards(
+	mut commands: Commands,
+	mut hazards: Query<(Entity, &ArenaHazard, &mut Transform), Without<PlayerBody>>,
+	player: Query<(Entity, &Transform, Option<&Evading>), (With<PlayerBody>, Without<Dead>)>,
+	fray: Query<&FrayMusic>,
+	mut ev_hit: EventWriter<EntityDamaged>,
+	mut ev_evaded: EventWriter<StrikeEvaded>,
+) {
+	let fray = fray.get_single().expect("Could not find fray");
+	let (player, player_transform, evading) = ok_or_return!(player.get_single());
+	let beat = fray.beat();
+
+	for (hazard_entity, hazard, mut transform) in hazards.iter_mut() {
+		if beat < hazard.erupt_beat {
+			let progress =
+				((beat - hazard.warn_beat) / (hazard.erupt_beat - hazard.warn_beat)) as f32;
+			let radius = progress.clamp(0.0, 1.0) * hazard.radius;
+			transform.scale = Vec3::new(radius, HAZARD_THICKNESS, radius);
+			continue;
+		}
+
+		commands.entity(hazard_entity).despawn_recursive();
+
+		if transform.translation.distance(player_transform.translation) > hazard.radius {
+			continue;
+		}
+
+		match evading.filter(|evading| evading.is_active(fray)) {
+			Some(Evading {
+				evasion: Evasion::Dodge,
+				..
+			}) => {
+				ev_evaded.send(StrikeEvaded {
+					attacker: hazard_entity,
+					evasion: Evasion::Dodge,
+				});
+			}
+			_ => {
+				ev_hit.send(EntityDamaged {
+					victim: player,
+					damage: hazard.damage,
+					fray_modifier: 1.0,
+					damage_type: DamageType::Fire,
+					effects: Vec::new(),
+					knockback: Vec3::ZERO,
+				});
+			}
+		}
+	}
+}
+
+pub fn tick_evasions(
+	mut commands: Commands,
+	mut evadings: Query<(Entity, &mut Evading)>,
+	fray: Query<&FrayMusic>,
+	time: Res<Time>,
+) {
+	let fray = fray.get_single().expect("Could not find fray");
+	for (entity, mut evading) in evadings.iter_mut() {
+		evading.time += time.delta();
+		if fray.time_to_bpm_beat(evading.time) as f32 > evading.window + evading.cooldown {
+			commands.entity(entity).remove::<Evading>();
+		}
+	}
+}
@@ -1,5 +1,7 @@
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 use bevy_rapier3d::prelude::Velocity;
+use serde::Deserialize;
 
 use crate::gravity::GravityRigidbodyBundle;
 use crate::util::{Billboard, DespawnTimer};
@@ -11,6 +13,32 @@ pub struct GelViscosity {
 	pub max: f32,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+pub enum DamageType {
+	#[default]
+	Blunt,
+	Slash,
+	Pierce,
+	Fire,
+	Ice,
+	Shock,
+}
+
+/// How much of each type of damage an entity shrugs off. Negative resistances take extra damage.
+#[derive(Component, Default)]
+pub struct Resistances(pub HashMap<DamageType, f32>);
+
+impl Resistances {
+	pub fn with(mut self, damage_type: DamageType, resistance: f32) -> Self {
+		self.0.insert(damage_type, resistance);
+		self
+	}
+
+	pub fn multiplier(&self, damage_type: DamageType) -> f32 {
+		1.0 - self.0.get(&damage_type).copied().unwrap_or(0.0)
+	}
+}
+
 #[derive(Component)]
 pub struct SpawnHealthBar;
 
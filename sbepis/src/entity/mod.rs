@@ -1,46 +1,196 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
+use bevy_rapier3d::prelude::{LockedAxes, Velocity};
+
+use crate::player_controller::PlayerBody;
 
+use self::attacks::*;
+pub use self::attacks::{BeatAttacker, ChartedAttacker, Evading, Evasion, StrikeEvaded};
 use self::health::*;
-pub use self::health::{GelViscosity, Healing, SpawnHealthBar};
+pub use self::health::{DamageType, GelViscosity, Healing, Resistances, SpawnHealthBar};
 use self::movement::*;
 pub use self::movement::{MovementInput, RandomInput, RotateTowardMovement, TargetPlayer};
 pub use self::orientation::GravityOrientation;
 use self::orientation::*;
+use self::status::*;
+pub use self::status::{StatusEffect, StatusEffects};
 
+pub mod attacks;
 pub mod health;
 pub mod movement;
 pub mod orientation;
 pub mod spawner;
+pub mod status;
+
+/// How hard a hit knocks its victim back for each point of damage it deals
+const KNOCKBACK_PER_DAMAGE: f32 = 8.0;
+/// How many beats a knocked back victim staggers for
+const HIT_STUN_BEATS: f32 = 0.5;
+/// How many seconds a killed entity tumbles around before it's despawned
+const DYING_DURATION: f32 = 1.5;
+/// How hard a killed entity gets spun when it goes limp
+const DYING_SPIN: f32 = 6.0;
 
 pub struct EntityPlugin;
 impl Plugin for EntityPlugin {
 	fn build(&self, app: &mut App) {
-		app.add_event::<EntityKilled>().add_systems(
-			Update,
-			(
-				orient,
-				random_vec2,
-				target_player,
-				strafe,
-				rotate_toward_movement,
-				spawn_health_bars,
-				despawn_invalid_health_bars,
-				update_health_bars_health,
-				update_health_bars_size,
-				heal,
-				kill_entities,
-			),
-		);
+		app.add_event::<EntityDamaged>()
+			.add_event::<EntityKilled>()
+			.add_event::<StrikeEvaded>()
+			.add_systems(Startup, load_telegraph_assets)
+			.add_systems(
+				Update,
+				(
+					orient,
+					random_vec2,
+					target_player,
+					strafe,
+					rotate_toward_movement,
+					spawn_health_bars,
+					despawn_invalid_health_bars,
+					update_health_bars_health,
+					update_health_bars_size,
+					heal,
+					tick_status_effects,
+					beat_attack,
+					chart_attack,
+					spawn_chart_hazards,
+					erupt_hazards,
+					tick_evasions,
+					deal_all_damage,
+					kill_entities,
+					update_dying,
+				),
+			);
 	}
 }
 
+#[derive(Event)]
+pub struct EntityDamaged {
+	pub victim: Entity,
+	pub damage: f32,
+	pub fray_modifier: f32,
+	pub damage_type: DamageType,
+	pub effects: Vec<StatusEffect>,
+	/// Which way to knock the victim. How far scales with the damage dealt, fray modifier included.
+	pub knockback: Vec3,
+}
+
+/// Sent on the hit that takes an entity's health to zero. The entity sticks around while it's `Dying`.
 #[derive(Event)]
 pub struct EntityKilled {
 	pub entity: Entity,
 }
 
-fn kill_entities(mut ev_killed: EventReader<EntityKilled>, mut commands: Commands) {
+/// A killed entity going limp and shrinking away before it's despawned.
+#[derive(Component)]
+pub struct Dying {
+	pub time: Duration,
+	scale: Vec3,
+}
+
+fn deal_all_damage(
+	mut ev_hit: EventReader<EntityDamaged>,
+	mut ev_kill: EventWriter<EntityKilled>,
+	mut healths: Query<(
+		&mut GelViscosity,
+		Option<&Resistances>,
+		Option<&mut StatusEffects>,
+		Option<&mut Velocity>,
+	)>,
+) {
+	for event in ev_hit.read() {
+		let Ok((mut health, resistances, mut statuses, velocity)) = healths.get_mut(event.victim)
+		else {
+			continue;
+		};
+		let damage = event.damage
+			* resistances.map_or(1.0, |resistances| resistances.multiplier(event.damage_type));
+
+		if let Some(statuses) = statuses.as_mut() {
+			for effect in event.effects.iter() {
+				statuses.apply(*effect);
+			}
+		}
+
+		if event.knockback != Vec3::ZERO {
+			if let Some(mut velocity) = velocity {
+				velocity.linvel += event.knockback * damage * KNOCKBACK_PER_DAMAGE;
+			}
+			if let Some(statuses) = statuses.as_mut() {
+				statuses.apply(StatusEffect::Stagger {
+					beats: HIT_STUN_BEATS,
+				});
+			}
+		}
+
+		let was_alive = health.value > 0.0;
+		health.value -= damage;
+
+		if was_alive && health.value <= 0.0 {
+			ev_kill.send(EntityKilled {
+				entity: event.victim,
+			});
+		}
+	}
+}
+
+fn kill_entities(
+	mut ev_killed: EventReader<EntityKilled>,
+	mut commands: Commands,
+	mut entities: Query<
+		(&Transform, Option<&mut Velocity>, Option<&BeatAttacker>),
+		(Without<PlayerBody>, Without<Dying>),
+	>,
+) {
 	for ev in ev_killed.read() {
-		commands.entity(ev.entity).despawn_recursive();
+		// The player respawns instead, and despawning them breaks everything
+		let Ok((transform, velocity, attacker)) = entities.get_mut(ev.entity) else {
+			continue;
+		};
+
+		if let Some(telegraph) = attacker.and_then(BeatAttacker::telegraph) {
+			commands.entity(telegraph).despawn_recursive();
+		}
+		if let Some(mut velocity) = velocity {
+			velocity.angvel += (rand::random::<Vec3>() * 2.0 - Vec3::ONE) * DYING_SPIN;
+		}
+
+		commands
+			.entity(ev.entity)
+			.remove::<(
+				MovementInput,
+				RandomInput,
+				TargetPlayer,
+				RotateTowardMovement,
+				GravityOrientation,
+				BeatAttacker,
+				ChartedAttacker,
+			)>()
+			.insert((
+				Dying {
+					time: Duration::ZERO,
+					scale: transform.scale,
+				},
+				LockedAxes::empty(),
+			));
+	}
+}
+
+fn update_dying(
+	mut commands: Commands,
+	mut dying: Query<(Entity, &mut Dying, &mut Transform)>,
+	time: Res<Time>,
+) {
+	for (entity, mut dying, mut transform) in dying.iter_mut() {
+		dying.time += time.delta();
+		let progress = dying.time.as_secs_f32() / DYING_DURATION;
+		if progress >= 1.0 {
+			commands.entity(entity).despawn_recursive();
+			continue;
+		}
+
+		transform.scale = dying.scale * (1.0 - progress * progress);
 	}
 }
@@ -5,13 +5,28 @@ use bevy_rapier3d::prelude::*;
 
 use crate::player_controller::PlayerBody;
 
+use super::status::StatusEffects;
+
 #[derive(Component, Deref, DerefMut, Default)]
 pub struct MovementInput(pub Vec3);
 
-pub fn strafe(mut bodies: Query<(&mut Velocity, &Transform, &MovementInput)>) {
-	for (mut velocity, transform, input) in bodies.iter_mut() {
+pub fn strafe(
+	mut bodies: Query<(
+		&mut Velocity,
+		&Transform,
+		&MovementInput,
+		Option<&StatusEffects>,
+	)>,
+) {
+	for (mut velocity, transform, input, statuses) in bodies.iter_mut() {
+		// Leave the body to its momentum while it's lost control
+		if statuses.is_some_and(|statuses| statuses.is_immobile()) {
+			continue;
+		}
+
+		let speed = statuses.map_or(1.0, |statuses| statuses.speed_multiplier());
 		velocity.linvel = velocity.linvel.project_onto(transform.up().into())
-			+ input.reject_from(transform.up().into());
+			+ input.reject_from(transform.up().into()) * speed;
 	}
 }
 
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::Velocity;
+use serde::Deserialize;
+
+use crate::fray::FrayMusic;
+
+use super::health::DamageType;
+use super::EntityDamaged;
+
+/// Lingering effects a hit can leave on whatever it damages. Durations are in beats.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum StatusEffect {
+	/// Deals damage on every beat
+	Bleed { damage_per_beat: f32, beats: f32 },
+	/// Can't move or attack
+	Stun { beats: f32 },
+	/// Can't move, but can still attack
+	Stagger { beats: f32 },
+	/// Gets launched upward and can't move until it's over
+	Knockback { strength: f32, beats: f32 },
+	/// Moves at a fraction of the usual speed
+	Slow { factor: f32, beats: f32 },
+}
+
+impl StatusEffect {
+	pub fn beats(&self) -> f32 {
+		match *self {
+			StatusEffect::Bleed { beats, .. } => beats,
+			StatusEffect::Stun { beats } => beats,
+			StatusEffect::Stagger { beats } => beats,
+			StatusEffect::Knockback { beats, .. } => beats,
+			StatusEffect::Slow { beats, .. } => beats,
+		}
+	}
+}
+
+struct ActiveStatus {
+	effect: StatusEffect,
+	/// Beats since the effect was applied
+	elapsed: f32,
+	started: bool,
+}
+
+#[derive(Component, Default)]
+pub struct StatusEffects {
+	active: Vec<ActiveStatus>,
+}
+
+impl StatusEffects {
+	pub fn apply(&mut self, effect: StatusEffect) {
+		self.active.push(ActiveStatus {
+			effect,
+			elapsed: 0.0,
+			started: false,
+		});
+	}
+
+	pub fn clear(&mut self) {
+		self.active.clear();
+	}
+
+	pub fn is_stunned(&self) -> bool {
+		self.active
+			.iter()
+			.any(|status| matches!(status.effect, StatusEffect::Stun { .. }))
+	}
+
+	/// Whether something has taken away control of this entity's movement.
+	pub fn is_immobile(&self) -> bool {
+		self.active.iter().any(|status| {
+			matches!(
+				status.effect,
+				StatusEffect::Stun { .. }
+					| StatusEffect::Stagger { .. }
+					| StatusEffect::Knockback { .. }
+			)
+		})
+	}
+
+	pub fn speed_multiplier(&self) -> f32 {
+		self.active
+			.iter()
+			.filter_map(|status| match status.effect {
+				StatusEffect::Slow { factor, .. } => Some(factor),
+				_ => None,
+			})
+			.product()
+	}
+}
+
+pub fn tick_status_effects(
+	mut entities: Query<(
+		Entity,
+		&mut StatusEffects,
+		&Transform,
+		Option<&mut Velocity>,
+	)>,
+	fray: Query<&FrayMusic>,
+	time: Res<Time>,
+	mut ev_hit: EventWriter<EntityDamaged>,
+) {
+	let fray = fray.get_single().expect("Could not find fray");
+	let delta = fray.time_to_bpm_beat(time.delta()) as f32;
+
+	for (entity, mut statuses, transform, mut velocity) in entities.iter_mut() {
+		for status in statuses.active.iter_mut() {
+			let prev_elapsed = status.elapsed;
+			status.elapsed += delta;
+
+			match status.effect {
+				StatusEffect::Bleed {
+					damage_per_beat,
+					beats,
+				} => {
+					let ticks = status.elapsed.min(beats).floor() - prev_elapsed.floor();
+					if ticks > 0.0 {
+						ev_hit.send(EntityDamaged {
+							victim: entity,
+							damage: damage_per_beat * ticks,
+							fray_modifier: 1.0,
+							damage_type: DamageType::Slash,
+							effects: Vec::new(),
+							knockback: Vec3::ZERO,
+						});
+					}
+				}
+				StatusEffect::Knockback { strength, .. } if !status.started => {
+					if let Some(velocity) = velocity.as_mut() {
+						velocity.linvel += transform.up() * strength;
+					}
+				}
+				_ => {}
+			}
+			status.started = true;
+		}
+
+		statuses
+			.active
+			.retain(|status| status.elapsed < status.effect.beats());
+	}
+}
@@ -0,0 +1,300 @@
+use std::fmt;
+use std::io;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use midly::num::u28;
+use midly::{MidiMessage, Smf, Timing, TrackEventKind};
+
+use super::{FrayMusic, MIDI_BEATS_PER_FRAY_BEAT};
+
+/// The midi channel designers author attack charts on. It gets stripped out before the synth sees it.
+pub const CHART_CHANNEL: u8 = 15;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChartEventKind {
+	/// Telegraphs an attack at the start of the note and strikes at the end. Authored as a C.
+	Attack,
+	/// Warns of a hazard under the player at the start of the note and erupts at the end. Authored as a D.
+	Hazard,
+}
+
+impl ChartEventKind {
+	fn from_key(key: u8) -> Option<Self> {
+		match key % 12 {
+			0 => Some(ChartEventKind::Attack),
+			2 => Some(ChartEventKind::Hazard),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ChartEvent {
+	pub kind: ChartEventKind,
+	/// Which group of entities this is for, authored as the note's octave
+	pub lane: u8,
+	pub beat: f64,
+	pub duration: f64,
+	/// The note's velocity, from 0 to 1
+	pub intensity: f32,
+}
+
+#[derive(Debug)]
+pub enum FrayChartError {
+	Parse(midly::Error),
+	/// Charts are in beats, so they can't be read from timecode timing
+	TimecodeTiming,
+	Write(io::Error),
+}
+
+impl fmt::Display for FrayChartError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			FrayChartError::Parse(error) => write!(f, "Couldn't parse fray midi: {error}"),
+			FrayChartError::TimecodeTiming => write!(f, "Fray midi needs metrical timing"),
+			FrayChartError::Write(error) => write!(f, "Couldn't write fray midi: {error}"),
+		}
+	}
+}
+
+/// Events authored on the chart channel of the active track, in fray beats.
+#[derive(Component)]
+pub struct FrayChart {
+	events: Vec<ChartEvent>,
+	length: f64,
+	last_beat: f64,
+}
+
+impl FrayChart {
+	/// Pulls the chart channel out of the midi, returning the chart and the midi without it.
+	pub fn from_midi(bytes: &[u8], channel: u8) -> Result<(Self, Vec<u8>), FrayChartError> {
+		let mut smf = Smf::parse(bytes).map_err(FrayChartError::Parse)?;
+		let ticks_per_beat = match smf.header.timing {
+			Timing::Metrical(ticks) => ticks.as_int() as f64 * MIDI_BEATS_PER_FRAY_BEAT,
+			Timing::Timecode(..) => return Err(FrayChartError::TimecodeTiming),
+		};
+
+		let mut events = Vec::new();
+		let mut length_ticks = 0;
+		for track in smf.tracks.iter_mut() {
+			let mut tick = 0;
+			let mut removed_delta = 0;
+			let mut held_notes: HashMap<u8, (u32, u8)> = HashMap::default();
+
+			track.retain_mut(|event| {
+				tick += event.delta.as_int();
+
+				let TrackEventKind::Midi {
+					channel: event_channel,
+					message,
+				} = event.kind
+				else {
+					event.delta = u28::new(event.delta.as_int() + removed_delta);
+					removed_delta = 0;
+					return true;
+				};
+				if event_channel.as_int() != channel {
+					event.delta = u28::new(event.delta.as_int() + removed_delta);
+					removed_delta = 0;
+					return true;
+				}
+
+				match message {
+					MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => {
+						held_notes.insert(key.as_int(), (tick, vel.as_int()));
+					}
+					MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => {
+						if let Some((start, vel)) = held_notes.remove(&key.as_int()) {
+							let key = key.as_int();
+							match ChartEventKind::from_key(key) {
+								Some(kind) => events.push(ChartEvent {
+									kind,
+									lane: key / 12,
+									beat: start as f64 / ticks_per_beat,
+									duration: (tick - start) as f64 / ticks_per_beat,
+									intensity: vel as f32 / 127.0,
+								}),
+								None => warn!("Unknown chart note {key}"),
+							}
+						}
+					}
+					_ => {}
+				}
+
+				removed_delta += event.delta.as_int();
+				false
+			});
+
+			length_ticks = length_ticks.max(tick);
+		}
+
+		events.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+
+		let mut music = Vec::new();
+		smf.write_std(&mut music).map_err(FrayChartError::Write)?;
+
+		Ok((
+			Self {
+				events,
+				length: length_ticks as f64 / ticks_per_beat,
+				last_beat: 0.0,
+			},
+			music,
+		))
+	}
+
+	pub fn has_lane(&self, lane: u8) -> bool {
+		self.events.iter().any(|event| event.lane == lane)
+	}
+
+	/// All events starting in `from..to`, accounting for the track looping.
+	pub fn events_between(&self, from: f64, to: f64) -> Vec<ChartEvent> {
+		if self.length <= 0.0 || to <= from {
+			return vec![];
+		}
+
+		let first_loop = (from / self.length).floor() as i64;
+		let last_loop = (to / self.length).floor() as i64;
+		(first_loop..=last_loop)
+			.flat_map(move |loop_index| {
+				let offset = loop_index as f64 * self.length;
+				self.events
+					.iter()
+					.map(move |event| ChartEvent {
+						beat: event.beat + offset,
+						..*event
+					})
+					.filter(move |event| (from..to).contains(&event.beat))
+			})
+			.collect()
+	}
+}
+
+#[derive(Event)]
+pub struct ChartEventFired(pub ChartEvent);
+
+pub fn fire_chart_events(
+	mut charts: Query<(&mut FrayChart, &FrayMusic)>,
+	mut ev_chart: EventWriter<ChartEventFired>,
+) {
+	for (mut chart, fray) in charts.iter_mut() {
+		let beat = fray.beat();
+		for event in chart.events_between(chart.last_beat, beat) {
+			ev_chart.send(ChartEventFired(event));
+		}
+		chart.last_beat = beat;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use midly::num::{u15, u4, u7};
+	use midly::{Format, Fps, Header, MetaMessage, TrackEvent};
+
+	use super::*;
+
+	const TICKS_PER_MIDI_BEAT: u16 = 96;
+	const MUSIC_CHANNEL: u8 = 0;
+
+	fn note(delta: u32, channel: u8, key: u8, vel: u8) -> TrackEvent<'static> {
+		TrackEvent {
+			delta: u28::new(delta),
+			kind: TrackEventKind::Midi {
+				channel: u4::new(channel),
+				message: MidiMessage::NoteOn {
+					key: u7::new(key),
+					vel: u7::new(vel),
+				},
+			},
+		}
+	}
+
+	fn midi_bytes(timing: Timing, mut track: Vec<TrackEvent<'static>>) -> Vec<u8> {
+		let mut smf = Smf::new(Header::new(Format::SingleTrack, timing));
+		track.push(TrackEvent {
+			delta: u28::new(0),
+			kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
+		});
+		smf.tracks.push(track);
+
+		let mut bytes = Vec::new();
+		smf.write_std(&mut bytes).unwrap();
+		bytes
+	}
+
+	/// A music note over the whole track, with an attack in lane 1 that lasts a fray beat, starting a fray beat in.
+	fn charted_track() -> Vec<u8> {
+		let fray_beat = TICKS_PER_MIDI_BEAT as u32 * MIDI_BEATS_PER_FRAY_BEAT as u32;
+		midi_bytes(
+			Timing::Metrical(u15::new(TICKS_PER_MIDI_BEAT)),
+			vec![
+				note(0, MUSIC_CHANNEL, 60, 100),
+				note(fray_beat, CHART_CHANNEL, 12, 127),
+				note(fray_beat, CHART_CHANNEL, 12, 0),
+				note(fray_beat, MUSIC_CHANNEL, 60, 0),
+			],
+		)
+	}
+
+	#[test]
+	fn chart_notes_become_events_in_fray_beats() {
+		let (chart, _) = FrayChart::from_midi(&charted_track(), CHART_CHANNEL).unwrap();
+
+		assert_eq!(chart.events.len(), 1);
+		let event = chart.events[0];
+		assert_eq!(event.kind, ChartEventKind::Attack);
+		assert_eq!(event.lane, 1);
+		assert_eq!(event.beat, 1.0);
+		assert_eq!(event.duration, 1.0);
+		assert_eq!(event.intensity, 1.0);
+		assert_eq!(chart.length, 3.0);
+	}
+
+	#[test]
+	fn chart_channel_is_stripped_without_moving_the_music() {
+		let (_, music) = FrayChart::from_midi(&charted_track(), CHART_CHANNEL).unwrap();
+		let smf = Smf::parse(&music).unwrap();
+
+		let mut tick = 0;
+		let mut music_notes = Vec::new();
+		for event in smf.tracks[0].iter() {
+			tick += event.delta.as_int();
+			if let TrackEventKind::Midi { channel, .. } = event.kind {
+				assert_ne!(channel.as_int(), CHART_CHANNEL);
+				music_notes.push(tick);
+			}
+		}
+		assert_eq!(music_notes, vec![0, TICKS_PER_MIDI_BEAT as u32 * 6]);
+	}
+
+	#[test]
+	fn chart_loops_with_the_track() {
+		let (chart, _) = FrayChart::from_midi(&charted_track(), CHART_CHANNEL).unwrap();
+
+		let beats: Vec<f64> = chart
+			.events_between(0.0, 8.0)
+			.iter()
+			.map(|event| event.beat)
+			.collect();
+		assert_eq!(beats, vec![1.0, 4.0, 7.0]);
+	}
+
+	#[test]
+	fn timecode_midi_is_rejected() {
+		let bytes = midi_bytes(Timing::Timecode(Fps::Fps24, 4), vec![]);
+
+		assert!(matches!(
+			FrayChart::from_midi(&bytes, CHART_CHANNEL),
+			Err(FrayChartError::TimecodeTiming)
+		));
+	}
+
+	#[test]
+	fn garbage_midi_is_rejected() {
+		assert!(matches!(
+			FrayChart::from_midi(b"not a midi file", CHART_CHANNEL),
+			Err(FrayChartError::Parse(_))
+		));
+	}
+}
@@ -0,0 +1,257 @@
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+
+use crate::camera::PlayerCameraNode;
+use crate::util::MapRange;
+
+use super::FrayMusic;
+
+const CROSSHAIR_SIZE: f32 = 6.0;
+const CROSSHAIR_PULSE_SIZE: f32 = 10.0;
+const RING_COUNT: u32 = 3;
+const RING_SPACING: f32 = 40.0;
+const RING_THICKNESS: f32 = 2.0;
+
+const LANE_WIDTH: f32 = 400.0;
+const LANE_HEIGHT: f32 = 24.0;
+const LANE_BEATS: f32 = 2.0;
+const LANE_TICKS: usize = 64;
+const LANE_TICK_WIDTH: f32 = 2.0;
+const LANE_MARKER_WIDTH: f32 = 4.0;
+
+/// How the weapon in hand lines up with the beat, so the HUD can draw its timing lane.
+#[derive(Resource, Default, PartialEq)]
+pub struct ActiveWeaponTiming {
+	pub name: String,
+	/// How many ticks the weapon cares about per beat
+	pub subdivisions: u32,
+	/// How many beats after clicking the weapon actually lands
+	pub impact_offset: f32,
+}
+
+#[derive(Component)]
+pub struct BeatCrosshair;
+
+#[derive(Component)]
+pub struct BeatRing {
+	pub beats_ahead: u32,
+}
+
+#[derive(Component)]
+pub struct WeaponTimingLane;
+
+#[derive(Component)]
+pub struct WeaponTimingLaneTick;
+
+#[derive(Component)]
+pub struct WeaponTimingLaneImpact;
+
+#[derive(Component)]
+pub struct WeaponTimingLaneLabel;
+
+fn screen_centered_style(size: f32) -> Style {
+	let mut style = Style {
+		position_type: PositionType::Absolute,
+		left: Val::Percent(50.0),
+		top: Val::Percent(50.0),
+		..default()
+	};
+	set_screen_centered_size(&mut style, size);
+	style
+}
+
+fn set_screen_centered_size(style: &mut Style, size: f32) {
+	style.width = Val::Px(size);
+	style.height = Val::Px(size);
+	style.margin.left = Val::Px(-size * 0.5);
+	style.margin.top = Val::Px(-size * 0.5);
+}
+
+pub fn spawn_beat_hud(mut commands: Commands) {
+	commands
+		.spawn((
+			Name::new("Beat HUD"),
+			NodeBundle {
+				style: Style {
+					position_type: PositionType::Absolute,
+					width: Val::Percent(100.0),
+					height: Val::Percent(100.0),
+					..default()
+				},
+				..default()
+			},
+			PlayerCameraNode,
+		))
+		.with_children(|parent| {
+			for beats_ahead in 0..RING_COUNT {
+				parent.spawn((
+					Name::new(format!("Beat Ring {beats_ahead}")),
+					NodeBundle {
+						style: Style {
+							border: UiRect::all(Val::Px(RING_THICKNESS)),
+							..screen_centered_style(CROSSHAIR_SIZE)
+						},
+						border_color: BorderColor(Color::NONE),
+						border_radius: BorderRadius::MAX,
+						..default()
+					},
+					BeatRing { beats_ahead },
+				));
+			}
+
+			parent.spawn((
+				Name::new("Beat Crosshair"),
+				NodeBundle {
+					style: screen_centered_style(CROSSHAIR_SIZE),
+					background_color: Color::WHITE.into(),
+					border_radius: BorderRadius::MAX,
+					..default()
+				},
+				BeatCrosshair,
+			));
+
+			parent
+				.spawn((
+					Name::new("Weapon Timing Lane"),
+					NodeBundle {
+						style: Style {
+							position_type: PositionType::Absolute,
+							left: Val::Percent(50.0),
+							bottom: Val::Px(40.0),
+							width: Val::Px(LANE_WIDTH),
+							height: Val::Px(LANE_HEIGHT),
+							margin: UiRect::left(Val::Px(-LANE_WIDTH * 0.5)),
+							..default()
+						},
+						background_color: css::BLACK.with_alpha(0.3).into(),
+						..default()
+					},
+					WeaponTimingLane,
+				))
+				.with_children(|parent| {
+					for _ in 0..LANE_TICKS {
+						parent.spawn((
+							Name::new("Weapon Timing Lane Tick"),
+							NodeBundle {
+								style: Style {
+									position_type: PositionType::Absolute,
+									width: Val::Px(LANE_TICK_WIDTH),
+									display: Display::None,
+									..default()
+								},
+								..default()
+							},
+							WeaponTimingLaneTick,
+						));
+					}
+
+					parent.spawn((
+						Name::new("Weapon Timing Lane Impact"),
+						NodeBundle {
+							style: Style {
+								position_type: PositionType::Absolute,
+								width: Val::Px(LANE_MARKER_WIDTH),
+								height: Val::Percent(100.0),
+								margin: UiRect::left(Val::Px(-LANE_MARKER_WIDTH * 0.5)),
+								..default()
+							},
+							background_color: css::ORANGE.into(),
+							..default()
+						},
+						WeaponTimingLaneImpact,
+					));
+
+					parent.spawn((
+						Name::new("Weapon Timing Lane Label"),
+						TextBundle::from_section("", TextStyle::default()).with_style(Style {
+							position_type: PositionType::Absolute,
+							bottom: Val::Px(LANE_HEIGHT),
+							left: Val::Px(0.0),
+							..default()
+						}),
+						WeaponTimingLaneLabel,
+					));
+				});
+		});
+}
+
+pub fn update_beat_crosshair(
+	fray: Query<&FrayMusic>,
+	mut crosshairs: Query<(&mut Style, &mut BackgroundColor), With<BeatCrosshair>>,
+) {
+	let fray = fray.get_single().expect("Could not find fray");
+	let fray_modifier = fray.modify_fray_damage(1.0);
+	let pulse = fray_modifier.map_range(0.0..FrayMusic::MAX_FRAY_MODIFIER, 0.0..1.0);
+
+	for (mut style, mut background_color) in crosshairs.iter_mut() {
+		set_screen_centered_size(&mut style, CROSSHAIR_SIZE + pulse * CROSSHAIR_PULSE_SIZE);
+		background_color.0 = Color::mix(
+			&Color::from(css::RED),
+			&Color::from(css::GREEN),
+			pulse.clamp(0.0, 1.0),
+		);
+	}
+}
+
+pub fn update_beat_rings(
+	fray: Query<&FrayMusic>,
+	mut rings: Query<(&BeatRing, &mut Style, &mut BorderColor)>,
+) {
+	let fray = fray.get_single().expect("Could not find fray");
+	let beat_progress = fray.beat_progress();
+
+	for (ring, mut style, mut border_color) in rings.iter_mut() {
+		let beats_until = ring.beats_ahead as f32 + 1.0 - beat_progress;
+		set_screen_centered_size(&mut style, CROSSHAIR_SIZE + beats_until * RING_SPACING);
+		border_color.0 =
+			Color::WHITE.with_alpha(beats_until.map_range(0.0..RING_COUNT as f32, 1.0..0.0));
+	}
+}
+
+pub fn update_weapon_timing_lane(
+	fray: Query<&FrayMusic>,
+	timing: Res<ActiveWeaponTiming>,
+	mut ticks: Query<(&mut Style, &mut BackgroundColor), With<WeaponTimingLaneTick>>,
+	mut impacts: Query<&mut Style, (With<WeaponTimingLaneImpact>, Without<WeaponTimingLaneTick>)>,
+	mut labels: Query<&mut Text, With<WeaponTimingLaneLabel>>,
+) {
+	let fray = fray.get_single().expect("Could not find fray");
+	let beat = fray.beat() as f32;
+	let subdivisions = timing.subdivisions.max(1);
+
+	let mut lane_ticks = ((beat * subdivisions as f32).ceil() as u32..)
+		.map(|tick| (tick, tick as f32 / subdivisions as f32))
+		.take_while(|(_, tick_beat)| *tick_beat < beat + LANE_BEATS);
+	for (mut style, mut background_color) in ticks.iter_mut() {
+		let Some((tick, tick_beat)) = lane_ticks.next() else {
+			style.display = Display::None;
+			continue;
+		};
+
+		let on_beat = tick % subdivisions == 0;
+		style.display = Display::Flex;
+		style.left = Val::Px((tick_beat - beat).map_range(0.0..LANE_BEATS, 0.0..LANE_WIDTH));
+		style.top = Val::Percent(if on_beat { 0.0 } else { 25.0 });
+		style.height = Val::Percent(if on_beat { 100.0 } else { 50.0 });
+		background_color.0 = if on_beat {
+			Color::WHITE
+		} else {
+			Color::from(css::GRAY)
+		};
+	}
+
+	for mut style in impacts.iter_mut() {
+		style.left = Val::Px(
+			timing
+				.impact_offset
+				.clamp(0.0, LANE_BEATS)
+				.map_range(0.0..LANE_BEATS, 0.0..LANE_WIDTH),
+		);
+	}
+
+	if timing.is_changed() {
+		for mut text in labels.iter_mut() {
+			text.sections[0].value.clone_from(&timing.name);
+		}
+	}
+}
@@ -8,28 +8,60 @@ use soundyrust::*;
 use crate::camera::PlayerCameraNode;
 use crate::util::MapRange;
 
+use self::chart::*;
+pub use self::chart::{ChartEventFired, ChartEventKind, FrayChart};
+pub use self::hud::ActiveWeaponTiming;
+use self::hud::*;
+
+mod chart;
+mod hud;
+
+/// The Fray counts one beat for every two beats of the midi.
+const MIDI_BEATS_PER_FRAY_BEAT: f64 = 2.0;
+
 pub struct FrayPlugin;
 
 impl Plugin for FrayPlugin {
 	fn build(&self, app: &mut App) {
 		app.add_plugins(SoundyPlugin)
-			.add_systems(Startup, play_background_music)
-			.add_systems(Update, tick_fray_music);
+			.init_resource::<ActiveWeaponTiming>()
+			.add_event::<ChartEventFired>()
+			.add_systems(Startup, (play_background_music, spawn_beat_hud))
+			.add_systems(
+				Update,
+				(
+					tick_fray_music,
+					(
+						fire_chart_events,
+						update_beat_crosshair,
+						update_beat_rings,
+						update_weapon_timing_lane,
+					)
+						.after(tick_fray_music),
+				),
+			);
 	}
 }
 
 fn play_background_music(mut commands: Commands, mut assets: ResMut<Assets<MidiAudio>>) {
-	commands.spawn((
+	let midi = include_bytes!("../../assets/fray.mid");
+	// Without a chart the music still plays, just with nothing attacking to it
+	let (chart, music) = match FrayChart::from_midi(midi, CHART_CHANNEL) {
+		Ok((chart, music)) => (Some(chart), music),
+		Err(error) => {
+			warn!("{error}");
+			(None, midi.to_vec())
+		}
+	};
+
+	let mut background_music = commands.spawn((
 		AudioSourceBundle {
 			source: assets.add(
-				MidiAudio::from_bytes(
-					include_bytes!("../assets/fray.mid"),
-					include_bytes!("../assets/hl4mgm.sf2"),
-				)
-				.with_channel_patch(0, 0, 46)
-				.with_channel_patch(1, 0, 3)
-				.with_channel_patch(2, 128, 0)
-				.with_channel_patch(3, 0, 0),
+				MidiAudio::from_bytes(&music, include_bytes!("../../assets/hl4mgm.sf2"))
+					.with_channel_patch(0, 0, 46)
+					.with_channel_patch(1, 0, 3)
+					.with_channel_patch(2, 128, 0)
+					.with_channel_patch(3, 0, 0),
 			),
 			settings: PlaybackSettings::LOOP
 				.with_volume(Volume::new(0.2))
@@ -38,6 +70,9 @@ fn play_background_music(mut commands: Commands, mut assets: ResMut<Assets<MidiA
 		Name::new("Background Music"),
 		FrayMusic::default(),
 	));
+	if let Some(chart) = chart {
+		background_music.insert(chart);
+	}
 
 	commands.spawn((
 		Name::new("Beat Counter"),
@@ -69,7 +104,7 @@ impl FrayMusic {
 	}
 
 	pub fn tick(&mut self, delta: Duration, midi_audio: &MidiAudio) {
-		self.beats_per_second = midi_audio.beats_per_second() / 2.0;
+		self.beats_per_second = midi_audio.beats_per_second() / MIDI_BEATS_PER_FRAY_BEAT;
 		self.beat += self.time_to_bpm_beat(delta);
 	}
 
@@ -77,10 +112,17 @@ impl FrayMusic {
 		(self.beat * divisions as f64).floor() as u32
 	}
 
+	pub fn beat(&self) -> f64 {
+		self.beat
+	}
+
 	pub fn beat_progress(&self) -> f32 {
 		self.beat.fract() as f32
 	}
 
+	/// The highest multiplier `modify_fray_damage` can give, right on the beat.
+	pub const MAX_FRAY_MODIFIER: f32 = 1.0 + 0.5 + 0.25 + 0.125;
+
 	pub fn modify_fray_damage(&self, damage: f32) -> f32 {
 		let modifier = self.single_beat_modifier(1.0)
 			+ self.single_beat_modifier(2.0)
@@ -17,23 +17,31 @@ pub fn input_manager_bundle<Action: Actionlike>(
 	input_map: InputMap<Action>,
 	start_enabled: bool,
 ) -> impl Bundle {
-	let mut action_state: ActionState<Action> = default();
-	if !start_enabled {
-		action_state.disable();
-	}
-
 	(
 		Name::new(format!(
 			"InputManager<{}>",
 			type_name::<Action>().split("::").last().unwrap()
 		)),
-		InputManagerBundle::<Action> {
-			input_map,
-			action_state,
-		},
+		input_manager(input_map, start_enabled),
 	)
 }
 
+/// The input manager on its own, for entities that need more than one or already have a `Name`.
+pub fn input_manager<Action: Actionlike>(
+	input_map: InputMap<Action>,
+	start_enabled: bool,
+) -> InputManagerBundle<Action> {
+	let mut action_state: ActionState<Action> = default();
+	if !start_enabled {
+		action_state.disable();
+	}
+
+	InputManagerBundle::<Action> {
+		input_map,
+		action_state,
+	}
+}
+
 pub fn action_event<Action: Actionlike + Copy, EventType: Event>(
 	event_generator: impl Fn(Action) -> EventType + Send + Sync + 'static,
 ) -> SystemConfigs {
@@ -0,0 +1,178 @@
+use std::fmt;
+use std::ops::{BitAnd, BitOr};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Every character a captcha code can use, in order of the six bits each one stands for.
+const CAPTCHA_CHARACTERS: &[u8; 64] =
+	b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!?";
+
+/// The 8-character code on the back of a captchalogue card, naming a kind of item.
+/// Each character is six bits, which is what alchemy combines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CaptchaCode([u8; CaptchaCode::LENGTH]);
+
+impl CaptchaCode {
+	pub const LENGTH: usize = 8;
+
+	/// Hashes a name into a code, so an item keeps its code as long as it keeps its name.
+	pub fn from_name(name: &str) -> Self {
+		// FNV-1a, since std's hashers aren't guaranteed to stay the same between releases
+		let mut hash: u64 = 0xcbf29ce484222325;
+		for byte in name.bytes() {
+			hash ^= byte as u64;
+			hash = hash.wrapping_mul(0x100000001b3);
+		}
+		Self::from_bits(hash)
+	}
+
+	/// Builds a code from the lowest 48 bits, first character first.
+	fn from_bits(bits: u64) -> Self {
+		let mut values = [0; Self::LENGTH];
+		for (index, value) in values.iter_mut().enumerate() {
+			let shift = 6 * (Self::LENGTH - 1 - index);
+			*value = ((bits >> shift) & 0x3f) as u8;
+		}
+		Self(values)
+	}
+
+	pub fn bits(self) -> u64 {
+		self.0
+			.iter()
+			.fold(0, |bits, &value| (bits << 6) | value as u64)
+	}
+}
+
+/// The `&&` of alchemy: only the holes punched in both cards.
+impl BitAnd for CaptchaCode {
+	type Output = Self;
+
+	fn bitand(self, other: Self) -> Self {
+		Self::from_bits(self.bits() & other.bits())
+	}
+}
+
+/// The `||` of alchemy: every hole punched in either card.
+impl BitOr for CaptchaCode {
+	type Output = Self;
+
+	fn bitor(self, other: Self) -> Self {
+		Self::from_bits(self.bits() | other.bits())
+	}
+}
+
+impl fmt::Display for CaptchaCode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for &value in &self.0 {
+			write!(f, "{}", CAPTCHA_CHARACTERS[value as usize] as char)?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug)]
+pub struct InvalidCaptchaCode(pub String);
+
+impl fmt::Display for InvalidCaptchaCode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"\"{}\" isn't {} characters of 0-9, A-Z, a-z, ! and ?",
+			self.0,
+			CaptchaCode::LENGTH
+		)
+	}
+}
+
+impl FromStr for CaptchaCode {
+	type Err = InvalidCaptchaCode;
+
+	fn from_str(code: &str) -> Result<Self, Self::Err> {
+		let invalid = || InvalidCaptchaCode(code.to_owned());
+		if code.len() != Self::LENGTH {
+			return Err(invalid());
+		}
+
+		let mut values = [0; Self::LENGTH];
+		for (value, character) in values.iter_mut().zip(code.bytes()) {
+			*value = CAPTCHA_CHARACTERS
+				.iter()
+				.position(|&allowed| allowed == character)
+				.ok_or_else(invalid)? as u8;
+		}
+		Ok(Self(values))
+	}
+}
+
+impl TryFrom<String> for CaptchaCode {
+	type Error = InvalidCaptchaCode;
+
+	fn try_from(code: String) -> Result<Self, Self::Error> {
+		code.parse()
+	}
+}
+
+impl From<CaptchaCode> for String {
+	fn from(code: CaptchaCode) -> Self {
+		code.to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn codes_survive_a_round_trip_through_text() {
+		for text in ["00000000", "!!!!!!!!", "Sburb4U?", "zZ9aA0!?"] {
+			let code: CaptchaCode = text.parse().unwrap();
+			assert_eq!(code.to_string(), text);
+		}
+	}
+
+	#[test]
+	fn codes_from_names_survive_a_round_trip_through_text() {
+		for name in ["Fork", "Hammer", "Pogo Hammer", ""] {
+			let code = CaptchaCode::from_name(name);
+			assert_eq!(code.to_string().parse::<CaptchaCode>().unwrap(), code);
+		}
+	}
+
+	#[test]
+	fn codes_from_names_stay_the_same() {
+		assert_eq!(
+			CaptchaCode::from_name("Fork"),
+			CaptchaCode::from_name("Fork")
+		);
+		assert_ne!(
+			CaptchaCode::from_name("Fork"),
+			CaptchaCode::from_name("fork")
+		);
+	}
+
+	#[test]
+	fn codes_of_the_wrong_length_are_rejected() {
+		assert!("".parse::<CaptchaCode>().is_err());
+		assert!("0000000".parse::<CaptchaCode>().is_err());
+		assert!("000000000".parse::<CaptchaCode>().is_err());
+	}
+
+	#[test]
+	fn codes_with_unknown_characters_are_rejected() {
+		assert!("0000 000".parse::<CaptchaCode>().is_err());
+		assert!("0000-000".parse::<CaptchaCode>().is_err());
+		// Eight bytes, but only seven characters
+		assert!("000000é".parse::<CaptchaCode>().is_err());
+	}
+
+	#[test]
+	fn rejected_codes_say_what_was_wrong() {
+		let error = "nope".parse::<CaptchaCode>().unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"\"nope\" isn't 8 characters of 0-9, A-Z, a-z, ! and ?"
+		);
+	}
+}
@@ -0,0 +1,184 @@
+use bevy::asset::LoadedFolder;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::player_controller::{Interactable, UpgradeMaterial, WeaponItem};
+use crate::{gridbox_material, some_or_continue, BoxBundle};
+
+use super::captcha::CaptchaCode;
+use super::usage::{Consumable, ThrowDamage};
+use super::Item;
+
+/// Where every `.item.ron` lives
+const ITEMS_FOLDER: &str = "items";
+/// Items at least this heavy take a moment to pick up
+const HEAVY_ITEM_WEIGHT: f32 = 3.0;
+const HEAVY_ITEM_PICKUP_SECONDS: f32 = 0.75;
+
+/// Everything needed to spawn a kind of item, loaded from an `.item.ron` file.
+#[derive(Asset, Clone, Debug, Deserialize, TypePath)]
+pub struct ItemDefinition {
+	/// Also what sylladex modi sort the item by
+	pub name: String,
+	/// Hashed from the name if left out
+	#[serde(default)]
+	pub code: Option<CaptchaCode>,
+	pub mesh: ItemMesh,
+	/// Which gridbox texture it's covered in
+	pub material: String,
+	/// Shown on its captchalogue card
+	pub icon: String,
+	#[serde(default)]
+	pub tags: Vec<String>,
+	/// How far the item reaches across in any direction
+	pub size: f32,
+	/// The mass of its rigidbody
+	#[serde(default = "ItemDefinition::default_weight")]
+	pub weight: f32,
+	/// Path to the `.weapon.ron` this item can be equipped as
+	#[serde(default)]
+	pub weapon: Option<String>,
+	/// Whether this item can be spent on weapon upgrades
+	#[serde(default)]
+	pub upgrade_material: bool,
+	/// What using it from the sylladex does, if it gets used up
+	#[serde(default)]
+	pub consumable: Option<Consumable>,
+	/// The damage it does when thrown into something
+	#[serde(default)]
+	pub throw_damage: Option<ThrowDamage>,
+}
+
+impl ItemDefinition {
+	fn default_weight() -> f32 {
+		1.0
+	}
+
+	pub fn code(&self) -> CaptchaCode {
+		self.code
+			.unwrap_or_else(|| CaptchaCode::from_name(&self.name))
+	}
+}
+
+/// The shape of an item, scaled to fit its `size`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum ItemMesh {
+	Cuboid,
+	Sphere,
+	Cylinder,
+	/// Standing up along its Y axis
+	Capsule,
+}
+
+impl ItemMesh {
+	pub fn mesh(self, size: f32) -> Mesh {
+		let half_size = size * 0.5;
+		match self {
+			ItemMesh::Cuboid => Cuboid::from_size(Vec3::splat(size)).into(),
+			ItemMesh::Sphere => Sphere::new(half_size).into(),
+			ItemMesh::Cylinder => Cylinder::new(half_size, size).into(),
+			ItemMesh::Capsule => Capsule3d::new(half_size * 0.5, half_size).into(),
+		}
+	}
+
+	pub fn collider(self, size: f32) -> Collider {
+		let half_size = size * 0.5;
+		match self {
+			ItemMesh::Cuboid => Collider::cuboid(half_size, half_size, half_size),
+			ItemMesh::Sphere => Collider::ball(half_size),
+			ItemMesh::Cylinder => Collider::cylinder(half_size, half_size),
+			ItemMesh::Capsule => Collider::capsule_y(half_size * 0.5, half_size * 0.5),
+		}
+	}
+}
+
+/// Every loaded item definition, by captcha code.
+#[derive(Resource, Default)]
+pub struct ItemCatalog {
+	/// Keeps everything in the items folder loaded. Never read, just held on to.
+	#[allow(dead_code)]
+	folder: Handle<LoadedFolder>,
+	definitions: HashMap<CaptchaCode, Handle<ItemDefinition>>,
+}
+
+impl ItemCatalog {
+	pub fn get(&self, code: CaptchaCode) -> Option<&Handle<ItemDefinition>> {
+		self.definitions.get(&code)
+	}
+
+	/// Files a definition straight away, instead of waiting for its asset event.
+	pub fn insert(&mut self, code: CaptchaCode, handle: Handle<ItemDefinition>) {
+		self.definitions.insert(code, handle);
+	}
+}
+
+pub fn load_item_definitions(mut catalog: ResMut<ItemCatalog>, asset_server: Res<AssetServer>) {
+	catalog.folder = asset_server.load_folder(ITEMS_FOLDER);
+}
+
+/// Files definitions under their codes as they load, including ones made at runtime.
+pub fn catalog_item_definitions(
+	mut ev_asset: EventReader<AssetEvent<ItemDefinition>>,
+	mut definitions: ResMut<Assets<ItemDefinition>>,
+	mut catalog: ResMut<ItemCatalog>,
+) {
+	for event in ev_asset.read() {
+		let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = event else {
+			continue;
+		};
+		let definition = some_or_continue!(definitions.get(*id));
+		let code = definition.code();
+		let name = definition.name.clone();
+		let handle = some_or_continue!(definitions.get_strong_handle(*id));
+
+		if let Some(other) = catalog.definitions.insert(code, handle) {
+			if other.id() != *id {
+				let other_name = definitions
+					.get(&other)
+					.map_or("another item", |other| other.name.as_str());
+				warn!("{name} has the same captcha code as {other_name}: {code}");
+			}
+		}
+	}
+}
+
+/// Puts a new item into the world, ready to be captchalogued.
+pub fn spawn_item(
+	commands: &mut Commands,
+	asset_server: &AssetServer,
+	meshes: &mut Assets<Mesh>,
+	materials: &mut Assets<StandardMaterial>,
+	definition: &ItemDefinition,
+	handle: Handle<ItemDefinition>,
+	position: Vec3,
+) -> Entity {
+	let mut item = commands.spawn((
+		Name::new(definition.name.clone()),
+		BoxBundle::new(
+			position,
+			meshes.add(definition.mesh.mesh(definition.size)),
+			gridbox_material(&definition.material, materials, asset_server),
+		)
+		.with_collider(definition.mesh.collider(definition.size)),
+		ColliderMassProperties::Mass(definition.weight),
+		Item {
+			definition: handle,
+			code: definition.code(),
+			icon: asset_server.load(definition.icon.clone()),
+		},
+	));
+	if definition.weight >= HEAVY_ITEM_WEIGHT {
+		item.insert(Interactable::new("Pick up").with_hold_duration(HEAVY_ITEM_PICKUP_SECONDS));
+	} else {
+		item.insert(Interactable::new("Pick up"));
+	}
+	if let Some(weapon) = &definition.weapon {
+		item.insert(WeaponItem(asset_server.load(weapon.clone())));
+	}
+	if definition.upgrade_material {
+		item.insert(UpgradeMaterial);
+	}
+	item.id()
+}
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::Velocity;
+
+use crate::camera::PlayerCamera;
+
+use super::usage::ThrownItem;
+use super::{release_item, Item, ItemDefinition, ItemDropped};
+
+/// Where held items sit relative to the camera
+const HOLD_OFFSET: Vec3 = Vec3::new(0.4, -0.3, -0.8);
+const THROW_SPEED: f32 = 8.0;
+
+/// An item out of the inventory and in the player's hand, ready to be thrown.
+#[derive(Component)]
+pub struct HeldItem;
+
+/// Puts an item in the player's hand, dropping whatever was there before.
+pub fn hold_item(
+	commands: &mut Commands,
+	held_items: &Query<Entity, With<HeldItem>>,
+	ev_dropped: &mut EventWriter<ItemDropped>,
+	item: Entity,
+) {
+	for held_item in held_items.iter() {
+		release_item(commands, held_item);
+		ev_dropped.send(ItemDropped(held_item));
+	}
+	commands
+		.entity(item)
+		.insert((HeldItem, Visibility::Inherited));
+}
+
+pub fn carry_held_items(
+	mut held_items: Query<&mut Transform, With<HeldItem>>,
+	player_camera: Query<&GlobalTransform, With<PlayerCamera>>,
+) {
+	let player_camera = player_camera.get_single().expect("Player camera not found");
+	let (_, rotation, _) = player_camera.to_scale_rotation_translation();
+	for mut transform in held_items.iter_mut() {
+		transform.translation = player_camera.transform_point(HOLD_OFFSET);
+		transform.rotation = rotation;
+	}
+}
+
+/// Throws held items forward. Ones with `throw_damage` hurt whatever they hit.
+pub fn throw_held_items(
+	mut commands: Commands,
+	held_items: Query<(Entity, &Item, &Transform), With<HeldItem>>,
+	player_camera: Query<&GlobalTransform, With<PlayerCamera>>,
+	definitions: Res<Assets<ItemDefinition>>,
+	mut ev_dropped: EventWriter<ItemDropped>,
+) {
+	let player_camera = player_camera.get_single().expect("Player camera not found");
+	for (item, item_info, transform) in held_items.iter() {
+		release_item(&mut commands, item);
+		commands
+			.entity(item)
+			.insert(Velocity::linear(player_camera.forward() * THROW_SPEED));
+		if let Some(thrown) = definitions
+			.get(&item_info.definition)
+			.and_then(|definition| ThrownItem::new(definition, transform.translation))
+		{
+			commands.entity(item).insert(thrown);
+		}
+		ev_dropped.send(ItemDropped(item));
+	}
+}
@@ -1,19 +1,51 @@
 use bevy::prelude::*;
+use bevy_common_assets::ron::RonAssetPlugin;
 use bevy_rapier3d::prelude::*;
+use definition::*;
+use hand::*;
 use screen::*;
+use usage::*;
 
 use crate::input::button_just_pressed;
 use crate::iter_system::*;
-use crate::menus::show_menu;
-use crate::player_controller::{interact_with, PlayerAction};
+use crate::menus::{show_menu, InputManagerMenuPlugin};
+use crate::ok_or_continue;
+use crate::player_controller::{interact_with, GameMode, PlayerAction, PlayerDied};
 
+pub use self::captcha::CaptchaCode;
+pub use self::definition::{spawn_item, ItemCatalog, ItemDefinition};
+use self::modus::{Card, Cards, FetchModus, StackModus};
+
+mod captcha;
+mod definition;
+mod hand;
+mod modus;
 mod screen;
+mod usage;
+
+/// How many captchalogue cards a sylladex has
+const CARD_COUNT: usize = 8;
+/// The most weight a sylladex can hold before it refuses anything else
+const MAX_WEIGHT: f32 = 10.0;
+/// How full a sylladex has to be, by weight, before it slows the player down
+const ENCUMBERED_FRACTION: f32 = 0.75;
 
 pub struct InventoryPlugin;
 impl Plugin for InventoryPlugin {
 	fn build(&self, app: &mut App) {
-		app.add_event::<ItemPickedUp>()
-			.add_systems(Startup, spawn_inventory_screen)
+		app.add_plugins(RonAssetPlugin::<ItemDefinition>::new(&["item.ron"]))
+			.add_event::<ItemPickedUp>()
+			.add_event::<ItemUsed>()
+			.add_event::<ItemRetrieved>()
+			.add_event::<ItemDropped>()
+			.add_event::<ItemPickupFailed>()
+			.init_resource::<SelectedInventoryItem>()
+			.init_resource::<DraggedInventoryItem>()
+			.init_resource::<InventoryFilter>()
+			.add_plugins(InputManagerMenuPlugin::<InventoryScreenAction>::default())
+			.init_resource::<ItemCatalog>()
+			.init_resource::<QuickItem>()
+			.add_systems(Startup, (spawn_inventory_screen, load_item_definitions))
 			.add_systems(
 				Update,
 				(
@@ -23,37 +55,296 @@ impl Plugin for InventoryPlugin {
 						.iter_done(),
 					show_menu::<InventoryScreen>
 						.run_if(button_just_pressed(PlayerAction::OpenInventory)),
-					add_item_to_inventory_screen,
+					update_inventory_screen,
+					click_inventory_items,
+					drop_dragged_items,
+					update_inventory_tooltip,
+					select_with_keys,
+					sort_inventory,
+					filter_inventory,
+					show_pickup_failures,
+					switch_fetch_modus,
+					act_on_selected_item,
+					carry_held_items,
+					throw_held_items.run_if(button_just_pressed(PlayerAction::Throw)),
+					drop_inventory_on_death,
+					catalog_item_definitions,
+				),
+			)
+			.add_systems(
+				Update,
+				(
+					consume_items,
+					wear_off_item_healing,
+					hit_with_thrown_items,
+					use_quick_item.run_if(button_just_pressed(PlayerAction::UseQuickItem)),
 				),
 			);
 	}
 }
 
-#[derive(Component, Default)]
+/// A sylladex: a row of captchalogue cards, and the fetch modus that decides how they get used.
+#[derive(Component)]
 pub struct Inventory {
-	pub items: Vec<Entity>,
+	cards: Cards,
+	modus: &'static dyn FetchModus,
+}
+
+impl Default for Inventory {
+	fn default() -> Self {
+		Self {
+			cards: vec![None; CARD_COUNT],
+			modus: &StackModus,
+		}
+	}
+}
+
+impl Inventory {
+	pub fn modus(&self) -> &'static dyn FetchModus {
+		self.modus
+	}
+
+	pub fn cards(&self) -> &Cards {
+		&self.cards
+	}
+
+	pub fn items(&self) -> impl Iterator<Item = Entity> + '_ {
+		self.cards.iter().flatten().map(|card| card.item)
+	}
+
+	pub fn weight(&self) -> f32 {
+		cards_weight(&self.cards)
+	}
+
+	pub fn is_encumbered(&self) -> bool {
+		self.weight() > MAX_WEIGHT * ENCUMBERED_FRACTION
+	}
+
+	/// Puts an item in a card if the modus allows it and it isn't too heavy. Returns whatever got ejected to make room.
+	pub fn captchalogue(
+		&mut self,
+		item: Entity,
+		name: String,
+		weight: f32,
+	) -> Result<Vec<Entity>, CaptchalogueError> {
+		// Try it on a copy first, since whatever gets ejected doesn't count towards the weight
+		let mut cards = self.cards.clone();
+		let ejected = self
+			.modus
+			.captchalogue(&mut cards, Card { item, name, weight })
+			.map_err(|_| CaptchalogueError::NoRoom)?;
+		if cards_weight(&cards) > MAX_WEIGHT {
+			return Err(CaptchalogueError::TooHeavy);
+		}
+
+		self.cards = cards;
+		Ok(ejected.into_iter().map(|card| card.item).collect())
+	}
+
+	/// Takes an item out if the modus allows it.
+	pub fn retrieve(&mut self, item: Entity) -> bool {
+		let Some(index) = self.card_index(item) else {
+			return false;
+		};
+		if !self.modus.can_retrieve(&self.cards, index) {
+			return false;
+		}
+
+		self.modus.retrieve(&mut self.cards, index);
+		true
+	}
+
+	/// Takes an item out no matter what the modus thinks, for when the game uses it up.
+	pub fn take(&mut self, item: Entity) {
+		if let Some(index) = self.card_index(item) {
+			self.modus.retrieve(&mut self.cards, index);
+		}
+	}
+
+	pub fn card(&self, item: Entity) -> Option<&Card> {
+		self.cards[self.card_index(item)?].as_ref()
+	}
+
+	/// Moves an item into another card, swapping it with whatever's there, if the modus allows it.
+	pub fn move_item(&mut self, item: Entity, index: usize) -> bool {
+		let Some(from) = self.card_index(item) else {
+			return false;
+		};
+		if !self.modus.can_rearrange() || index >= self.cards.len() || index == from {
+			return false;
+		}
+
+		self.cards.swap(from, index);
+		true
+	}
+
+	/// Sorts the cards by `key`, with the empty ones last, if the modus allows it.
+	pub fn sort_cards<K: Ord>(&mut self, mut key: impl FnMut(&Card) -> K) -> bool {
+		if !self.modus.can_rearrange() {
+			return false;
+		}
+
+		self.cards
+			.sort_by_cached_key(|card| (card.is_none(), card.as_ref().map(&mut key)));
+		true
+	}
+
+	fn card_index(&self, item: Entity) -> Option<usize> {
+		self.cards
+			.iter()
+			.position(|card| card.as_ref().is_some_and(|card| card.item == item))
+	}
+
+	/// Empties every card.
+	pub fn eject_all(&mut self) -> Vec<Entity> {
+		self.cards
+			.iter_mut()
+			.filter_map(Option::take)
+			.map(|card| card.item)
+			.collect()
+	}
+
+	/// Switches to another modus, re-captchaloguing everything under its rules. Returns whatever didn't fit.
+	pub fn set_modus(&mut self, modus: &'static dyn FetchModus) -> Vec<Entity> {
+		let cards: Vec<Card> = self.cards.iter_mut().filter_map(Option::take).collect();
+		self.modus = modus;
+
+		let mut ejected = Vec::new();
+		for card in cards {
+			match modus.captchalogue(&mut self.cards, card) {
+				Ok(overflow) => ejected.extend(overflow.into_iter().map(|card| card.item)),
+				Err(card) => ejected.push(card.item),
+			}
+		}
+		ejected
+	}
 }
 
+fn cards_weight(cards: &Cards) -> f32 {
+	cards.iter().flatten().map(|card| card.weight).sum()
+}
+
+/// Why an item wouldn't go into the sylladex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptchalogueError {
+	/// The modus has nowhere to put it
+	NoRoom,
+	/// It would put the sylladex over `MAX_WEIGHT`
+	TooHeavy,
+}
+
+/// Something that can be captchalogued. Spawned from an `ItemDefinition` with `spawn_item`.
 #[derive(Component)]
 pub struct Item {
+	pub definition: Handle<ItemDefinition>,
+	pub code: CaptchaCode,
 	pub icon: Handle<Image>,
 }
 
 #[derive(Event)]
 pub struct ItemPickedUp(pub Entity);
 
+/// Sent when the player uses an item from the inventory screen. What that does is up to whoever handles it.
+#[derive(Event)]
+pub struct ItemUsed(pub Entity);
+
+/// Sent when the player takes an item out of the inventory and into their hand.
+#[derive(Event)]
+pub struct ItemRetrieved(pub Entity);
+
+/// Sent when the player drops or throws an item back into the world.
+#[derive(Event)]
+pub struct ItemDropped(pub Entity);
+
+/// Sent when the player tries to pick up an item that won't go into their sylladex.
+#[derive(Event)]
+pub struct ItemPickupFailed {
+	pub name: String,
+	pub reason: CaptchalogueError,
+}
+
 fn pick_up_items(
 	In(item_entity): In<Entity>,
 	mut commands: Commands,
-	mut player: Query<&mut Inventory>,
+	mut player: Query<(&mut Inventory, &GlobalTransform)>,
+	mut transforms: Query<&mut Transform, Without<Inventory>>,
+	names: Query<&Name>,
+	items: Query<&Item>,
+	definitions: Res<Assets<ItemDefinition>>,
 	mut ev_picked_up: EventWriter<ItemPickedUp>,
+	mut ev_pickup_failed: EventWriter<ItemPickupFailed>,
 ) {
-	let mut inventory = player.single_mut();
-	inventory.items.push(item_entity);
+	let (mut inventory, body_transform) = player.single_mut();
+	let name = names
+		.get(item_entity)
+		.map_or_else(|_| "Item".to_owned(), |name| name.to_string());
+
+	let weight = items
+		.get(item_entity)
+		.ok()
+		.and_then(|item| definitions.get(&item.definition))
+		.map_or(0.0, |definition| definition.weight);
+
+	// The modus gets the final say on whether it fits
+	let ejected = match inventory.captchalogue(item_entity, name.clone(), weight) {
+		Ok(ejected) => ejected,
+		Err(reason) => {
+			ev_pickup_failed.send(ItemPickupFailed { name, reason });
+			return;
+		}
+	};
 	commands
 		.entity(item_entity)
 		.remove::<RigidBody>()
 		.insert(Visibility::Hidden)
 		.insert(ColliderDisabled);
+	for ejected_item in ejected {
+		eject_item(
+			&mut commands,
+			&mut transforms,
+			ejected_item,
+			body_transform.translation(),
+		);
+	}
 	ev_picked_up.send(ItemPickedUp(item_entity));
 }
+
+/// Puts an item back into the world somewhere around `position`.
+fn eject_item(
+	commands: &mut Commands,
+	transforms: &mut Query<&mut Transform, Without<Inventory>>,
+	item: Entity,
+	position: Vec3,
+) {
+	if let Ok(mut transform) = transforms.get_mut(item) {
+		transform.translation = position + rand::random::<Vec3>() - Vec3::splat(0.5);
+	}
+	release_item(commands, item);
+}
+
+/// Gives an item back its physics and makes it visible again, right where its transform is.
+fn release_item(commands: &mut Commands, item: Entity) {
+	commands
+		.entity(item)
+		.remove::<(HeldItem, ColliderDisabled)>()
+		.insert((RigidBody::Dynamic, Velocity::zero(), Visibility::Inherited));
+}
+
+fn drop_inventory_on_death(
+	mut ev_died: EventReader<PlayerDied>,
+	mut commands: Commands,
+	mut inventories: Query<&mut Inventory>,
+	mut transforms: Query<&mut Transform, Without<Inventory>>,
+	game_mode: Res<GameMode>,
+) {
+	if !game_mode.death_penalties().drop_inventory {
+		return;
+	}
+
+	for ev in ev_died.read() {
+		let mut inventory = ok_or_continue!(inventories.get_mut(ev.body));
+		for item_entity in inventory.eject_all() {
+			eject_item(&mut commands, &mut transforms, item_entity, ev.position);
+		}
+	}
+}
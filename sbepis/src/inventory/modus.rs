@@ -0,0 +1,497 @@
+use bevy::prelude::*;
+use rand::seq::IteratorRandom;
+
+/// An item held in a captchalogue card, along with the name some modi sort it by.
+#[derive(Clone, Debug)]
+pub struct Card {
+	pub item: Entity,
+	pub name: String,
+	/// How much the item counts towards what the sylladex can carry
+	pub weight: f32,
+}
+
+pub type Cards = Vec<Option<Card>>;
+
+/// Every fetch modus the player can switch between, in the order they're shown.
+pub const FETCH_MODI: [&dyn FetchModus; 6] = [
+	&StackModus,
+	&QueueModus,
+	&ArrayModus,
+	&TreeModus,
+	&HashMapModus,
+	&MemoryModus,
+];
+
+/// How a modus's cards get arranged on the inventory screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModusLayout {
+	/// Every card in order, wrapping onto new rows
+	Grid,
+	/// Like `Grid`, but nobody can see what's on the cards
+	FaceDown,
+	/// Top of the stack first
+	Column,
+	/// Front of the queue first
+	Row,
+	/// Each row is one level deeper, with card `n`'s children at `2n + 1` and `2n + 2`
+	Tree,
+}
+
+/// The rules a sylladex follows for where items go in and which ones can come out.
+pub trait FetchModus: Send + Sync {
+	fn name(&self) -> &'static str;
+
+	fn layout(&self) -> ModusLayout;
+
+	/// Puts an item into the cards. Returns whatever got ejected to make room, or gives the card back if it won't fit at all.
+	fn captchalogue(&self, cards: &mut Cards, card: Card) -> Result<Vec<Card>, Card>;
+
+	/// Whether the player is allowed to take out the item in this card.
+	fn can_retrieve(&self, cards: &Cards, index: usize) -> bool;
+
+	/// Takes the item out of a card, keeping the rest in whatever order the modus needs. Doesn't check `can_retrieve`.
+	fn retrieve(&self, cards: &mut Cards, index: usize) -> Option<Card> {
+		cards.get_mut(index)?.take()
+	}
+
+	/// Whether the player can move cards around and sort them however they like.
+	fn can_rearrange(&self) -> bool {
+		false
+	}
+}
+
+/// Last in, first out. Overflowing pushes the bottom card out.
+pub struct StackModus;
+impl FetchModus for StackModus {
+	fn name(&self) -> &'static str {
+		"Stack"
+	}
+
+	fn layout(&self) -> ModusLayout {
+		ModusLayout::Column
+	}
+
+	fn captchalogue(&self, cards: &mut Cards, card: Card) -> Result<Vec<Card>, Card> {
+		cards.insert(0, Some(card));
+		Ok(cards.pop().flatten().into_iter().collect())
+	}
+
+	fn can_retrieve(&self, cards: &Cards, index: usize) -> bool {
+		index == 0 && cards.first().is_some_and(Option::is_some)
+	}
+
+	fn retrieve(&self, cards: &mut Cards, index: usize) -> Option<Card> {
+		compact_retrieve(cards, index)
+	}
+}
+
+/// First in, first out. Overflowing pushes the front card out.
+pub struct QueueModus;
+impl FetchModus for QueueModus {
+	fn name(&self) -> &'static str {
+		"Queue"
+	}
+
+	fn layout(&self) -> ModusLayout {
+		ModusLayout::Row
+	}
+
+	fn captchalogue(&self, cards: &mut Cards, card: Card) -> Result<Vec<Card>, Card> {
+		if let Some(empty) = cards.iter_mut().find(|card| card.is_none()) {
+			*empty = Some(card);
+			return Ok(Vec::new());
+		}
+		if cards.is_empty() {
+			return Err(card);
+		}
+
+		let front = cards.remove(0);
+		cards.push(Some(card));
+		Ok(front.into_iter().collect())
+	}
+
+	fn can_retrieve(&self, cards: &Cards, index: usize) -> bool {
+		index == 0 && cards.first().is_some_and(Option::is_some)
+	}
+
+	fn retrieve(&self, cards: &mut Cards, index: usize) -> Option<Card> {
+		compact_retrieve(cards, index)
+	}
+}
+
+/// Any card can be used in any order, but nothing fits once they're full.
+pub struct ArrayModus;
+impl FetchModus for ArrayModus {
+	fn name(&self) -> &'static str {
+		"Array"
+	}
+
+	fn layout(&self) -> ModusLayout {
+		ModusLayout::Grid
+	}
+
+	fn captchalogue(&self, cards: &mut Cards, card: Card) -> Result<Vec<Card>, Card> {
+		match cards.iter_mut().find(|card| card.is_none()) {
+			Some(empty) => {
+				*empty = Some(card);
+				Ok(Vec::new())
+			}
+			None => Err(card),
+		}
+	}
+
+	fn can_retrieve(&self, cards: &Cards, index: usize) -> bool {
+		cards.get(index).is_some_and(Option::is_some)
+	}
+
+	fn can_rearrange(&self) -> bool {
+		true
+	}
+}
+
+/// Sorts items into a binary tree by name. Only leaves can be taken out, and nothing fits past the deepest card.
+pub struct TreeModus;
+impl FetchModus for TreeModus {
+	fn name(&self) -> &'static str {
+		"Tree"
+	}
+
+	fn layout(&self) -> ModusLayout {
+		ModusLayout::Tree
+	}
+
+	fn captchalogue(&self, cards: &mut Cards, card: Card) -> Result<Vec<Card>, Card> {
+		let mut index = 0;
+		while let Some(slot) = cards.get_mut(index) {
+			match slot {
+				None => {
+					*slot = Some(card);
+					return Ok(Vec::new());
+				}
+				Some(node) => {
+					index = if card.name < node.name {
+						index * 2 + 1
+					} else {
+						index * 2 + 2
+					};
+				}
+			}
+		}
+		Err(card)
+	}
+
+	fn can_retrieve(&self, cards: &Cards, index: usize) -> bool {
+		let occupied = |index: usize| cards.get(index).is_some_and(Option::is_some);
+		occupied(index) && !occupied(index * 2 + 1) && !occupied(index * 2 + 2)
+	}
+}
+
+/// Hashes each item's name to pick its card, ejecting whatever was already there.
+pub struct HashMapModus;
+impl FetchModus for HashMapModus {
+	fn name(&self) -> &'static str {
+		"Hash Map"
+	}
+
+	fn layout(&self) -> ModusLayout {
+		ModusLayout::Grid
+	}
+
+	fn captchalogue(&self, cards: &mut Cards, card: Card) -> Result<Vec<Card>, Card> {
+		if cards.is_empty() {
+			return Err(card);
+		}
+
+		// Vowels are worth 1 and consonants 2, like in the comic
+		let hash: usize = card
+			.name
+			.chars()
+			.filter(char::is_ascii_alphabetic)
+			.map(|letter| {
+				if "aeiou".contains(letter.to_ascii_lowercase()) {
+					1
+				} else {
+					2
+				}
+			})
+			.sum();
+		let index = hash % cards.len();
+		Ok(cards[index].replace(card).into_iter().collect())
+	}
+
+	fn can_retrieve(&self, cards: &Cards, index: usize) -> bool {
+		cards.get(index).is_some_and(Option::is_some)
+	}
+}
+
+/// Shuffles items into the cards face down. Overflowing ejects a random card.
+pub struct MemoryModus;
+impl FetchModus for MemoryModus {
+	fn name(&self) -> &'static str {
+		"Memory"
+	}
+
+	fn layout(&self) -> ModusLayout {
+		ModusLayout::FaceDown
+	}
+
+	fn captchalogue(&self, cards: &mut Cards, card: Card) -> Result<Vec<Card>, Card> {
+		let mut rng = rand::thread_rng();
+		let index = cards
+			.iter()
+			.enumerate()
+			.filter(|(_, card)| card.is_none())
+			.map(|(index, _)| index)
+			.choose(&mut rng)
+			.or_else(|| (0..cards.len()).choose(&mut rng));
+		match index {
+			Some(index) => Ok(cards[index].replace(card).into_iter().collect()),
+			None => Err(card),
+		}
+	}
+
+	fn can_retrieve(&self, cards: &Cards, index: usize) -> bool {
+		cards.get(index).is_some_and(Option::is_some)
+	}
+}
+
+/// Takes a card out and slides the ones after it up to fill the gap.
+fn compact_retrieve(cards: &mut Cards, index: usize) -> Option<Card> {
+	if index >= cards.len() {
+		return None;
+	}
+
+	let card = cards.remove(index);
+	cards.push(None);
+	card
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn card(name: &str) -> Card {
+		Card {
+			item: Entity::from_raw(name.len() as u32),
+			name: name.to_owned(),
+			weight: 1.0,
+		}
+	}
+
+	fn empty_cards(count: usize) -> Cards {
+		vec![None; count]
+	}
+
+	fn names(cards: &Cards) -> Vec<Option<&str>> {
+		cards
+			.iter()
+			.map(|card| card.as_ref().map(|card| card.name.as_str()))
+			.collect()
+	}
+
+	fn fill(modus: &dyn FetchModus, cards: &mut Cards, names: &[&str]) {
+		for name in names {
+			modus.captchalogue(cards, card(name)).unwrap();
+		}
+	}
+
+	#[test]
+	fn stack_only_gives_back_the_top_card() {
+		let mut cards = empty_cards(3);
+		fill(&StackModus, &mut cards, &["a", "b"]);
+
+		assert_eq!(names(&cards), vec![Some("b"), Some("a"), None]);
+		assert!(StackModus.can_retrieve(&cards, 0));
+		assert!(!StackModus.can_retrieve(&cards, 1));
+		assert!(!StackModus.can_retrieve(&cards, 2));
+
+		assert_eq!(StackModus.retrieve(&mut cards, 0).unwrap().name, "b");
+		assert_eq!(names(&cards), vec![Some("a"), None, None]);
+	}
+
+	#[test]
+	fn stack_overflow_pushes_out_the_bottom_card() {
+		let mut cards = empty_cards(2);
+		fill(&StackModus, &mut cards, &["a", "b"]);
+
+		let ejected = StackModus.captchalogue(&mut cards, card("c")).unwrap();
+
+		assert_eq!(ejected.len(), 1);
+		assert_eq!(ejected[0].name, "a");
+		assert_eq!(names(&cards), vec![Some("c"), Some("b")]);
+	}
+
+	#[test]
+	fn queue_only_gives_back_the_front_card() {
+		let mut cards = empty_cards(3);
+		fill(&QueueModus, &mut cards, &["a", "b"]);
+
+		assert_eq!(names(&cards), vec![Some("a"), Some("b"), None]);
+		assert!(QueueModus.can_retrieve(&cards, 0));
+		assert!(!QueueModus.can_retrieve(&cards, 1));
+
+		assert_eq!(QueueModus.retrieve(&mut cards, 0).unwrap().name, "a");
+		assert_eq!(names(&cards), vec![Some("b"), None, None]);
+	}
+
+	#[test]
+	fn queue_overflow_pushes_out_the_front_card() {
+		let mut cards = empty_cards(2);
+		fill(&QueueModus, &mut cards, &["a", "b"]);
+
+		let ejected = QueueModus.captchalogue(&mut cards, card("c")).unwrap();
+
+		assert_eq!(ejected.len(), 1);
+		assert_eq!(ejected[0].name, "a");
+		assert_eq!(names(&cards), vec![Some("b"), Some("c")]);
+	}
+
+	#[test]
+	fn queue_without_cards_gives_the_item_back() {
+		let mut cards = empty_cards(0);
+
+		assert_eq!(
+			QueueModus
+				.captchalogue(&mut cards, card("a"))
+				.unwrap_err()
+				.name,
+			"a"
+		);
+	}
+
+	#[test]
+	fn array_gives_back_any_card_and_leaves_a_gap() {
+		let mut cards = empty_cards(3);
+		fill(&ArrayModus, &mut cards, &["a", "b", "c"]);
+
+		assert!((0..3).all(|index| ArrayModus.can_retrieve(&cards, index)));
+
+		assert_eq!(ArrayModus.retrieve(&mut cards, 1).unwrap().name, "b");
+		assert_eq!(names(&cards), vec![Some("a"), None, Some("c")]);
+		assert!(!ArrayModus.can_retrieve(&cards, 1));
+
+		fill(&ArrayModus, &mut cards, &["d"]);
+		assert_eq!(names(&cards), vec![Some("a"), Some("d"), Some("c")]);
+	}
+
+	#[test]
+	fn full_array_gives_the_item_back() {
+		let mut cards = empty_cards(1);
+		fill(&ArrayModus, &mut cards, &["a"]);
+
+		assert_eq!(
+			ArrayModus
+				.captchalogue(&mut cards, card("b"))
+				.unwrap_err()
+				.name,
+			"b"
+		);
+		assert_eq!(names(&cards), vec![Some("a")]);
+	}
+
+	#[test]
+	fn tree_sorts_items_by_name() {
+		let mut cards = empty_cards(7);
+		fill(&TreeModus, &mut cards, &["m", "c", "x", "a", "e", "z"]);
+
+		assert_eq!(
+			names(&cards),
+			vec![
+				Some("m"),
+				Some("c"),
+				Some("x"),
+				Some("a"),
+				Some("e"),
+				None,
+				Some("z")
+			]
+		);
+	}
+
+	#[test]
+	fn tree_only_gives_back_leaves() {
+		let mut cards = empty_cards(7);
+		fill(&TreeModus, &mut cards, &["m", "c", "x", "a"]);
+
+		assert!(!TreeModus.can_retrieve(&cards, 0));
+		assert!(!TreeModus.can_retrieve(&cards, 1));
+		assert!(TreeModus.can_retrieve(&cards, 2));
+		assert!(TreeModus.can_retrieve(&cards, 3));
+		assert!(!TreeModus.can_retrieve(&cards, 4));
+
+		assert_eq!(TreeModus.retrieve(&mut cards, 3).unwrap().name, "a");
+		assert!(TreeModus.can_retrieve(&cards, 1));
+	}
+
+	#[test]
+	fn tree_gives_the_item_back_past_the_deepest_card() {
+		let mut cards = empty_cards(3);
+		fill(&TreeModus, &mut cards, &["m", "c", "x"]);
+
+		assert_eq!(
+			TreeModus
+				.captchalogue(&mut cards, card("a"))
+				.unwrap_err()
+				.name,
+			"a"
+		);
+		assert_eq!(names(&cards), vec![Some("m"), Some("c"), Some("x")]);
+	}
+
+	#[test]
+	fn hash_map_picks_cards_by_vowels_and_consonants() {
+		let mut cards = empty_cards(4);
+		// "a" hashes to 1 and "b" to 2
+		fill(&HashMapModus, &mut cards, &["a", "b"]);
+
+		assert_eq!(names(&cards), vec![None, Some("a"), Some("b"), None]);
+		assert!(HashMapModus.can_retrieve(&cards, 1));
+		assert!(!HashMapModus.can_retrieve(&cards, 0));
+	}
+
+	#[test]
+	fn hash_map_collisions_eject_the_old_item() {
+		let mut cards = empty_cards(4);
+		// Both hash to 3, since only which letters are vowels matters
+		fill(&HashMapModus, &mut cards, &["ab"]);
+
+		let ejected = HashMapModus.captchalogue(&mut cards, card("ba")).unwrap();
+
+		assert_eq!(ejected.len(), 1);
+		assert_eq!(ejected[0].name, "ab");
+		assert_eq!(names(&cards), vec![None, None, None, Some("ba")]);
+	}
+
+	#[test]
+	fn hash_map_ignores_case_and_non_letters() {
+		let mut cards = empty_cards(8);
+		fill(&HashMapModus, &mut cards, &["A-B 2"]);
+
+		assert_eq!(cards[3].as_ref().unwrap().name, "A-B 2");
+	}
+
+	#[test]
+	fn memory_fills_empty_cards_before_ejecting_anything() {
+		let mut cards = empty_cards(3);
+		fill(&MemoryModus, &mut cards, &["a", "b", "c"]);
+
+		assert!(cards.iter().all(Option::is_some));
+
+		let ejected = MemoryModus.captchalogue(&mut cards, card("d")).unwrap();
+		assert_eq!(ejected.len(), 1);
+		assert!(cards.iter().flatten().any(|card| card.name == "d"));
+	}
+
+	#[test]
+	fn memory_without_cards_gives_the_item_back() {
+		let mut cards = empty_cards(0);
+
+		assert_eq!(
+			MemoryModus
+				.captchalogue(&mut cards, card("a"))
+				.unwrap_err()
+				.name,
+			"a"
+		);
+	}
+}
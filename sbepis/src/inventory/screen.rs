@@ -1,69 +1,857 @@
 use bevy::color::palettes::css;
 use bevy::prelude::*;
-use leafwing_input_manager::prelude::InputMap;
+use bevy::window::PrimaryWindow;
+use leafwing_input_manager::prelude::{ActionState, InputMap};
+use leafwing_input_manager::{Actionlike, InputControlKind};
 
-use crate::camera::PlayerCameraNode;
-use crate::input::input_manager_bundle;
+use crate::camera::{PlayerCamera, PlayerCameraNode};
+use crate::input::input_manager;
 use crate::menus::*;
+use crate::util::DespawnTimer;
+use crate::{ok_or_return, some_or_return};
 
-use super::{Item, ItemPickedUp};
+use super::definition::ItemDefinition;
+use super::hand::{hold_item, HeldItem};
+use super::modus::{ModusLayout, FETCH_MODI};
+use super::usage::QuickItem;
+use super::{
+	eject_item, release_item, CaptchalogueError, Inventory, Item, ItemDropped, ItemPickupFailed,
+	ItemRetrieved, ItemUsed, MAX_WEIGHT,
+};
+
+const CARD_SIZE: f32 = 100.0;
+const CARD_BORDER: f32 = 4.0;
+const CARD_GAP: f32 = 10.0;
+/// How far in front of the camera dropped items appear
+const DROP_DISTANCE: f32 = 1.0;
+/// How far the tooltip sits from the cursor
+const TOOLTIP_OFFSET: f32 = 16.0;
+/// Seconds a failed pickup stays on screen
+const PICKUP_FAILURE_DURATION: f32 = 2.0;
 
 #[derive(Component)]
 pub struct InventoryScreen;
 
+/// A button on the inventory screen for one of the items in the inventory.
+#[derive(Component)]
+pub struct InventoryScreenItem(pub Entity);
+
+/// A card on the inventory screen, by its index in the sylladex. Empty cards have one too, so items can be dragged onto them.
+#[derive(Component)]
+pub struct InventoryScreenCard(pub usize);
+
+/// A button on the inventory screen that switches to one of the `FETCH_MODI`.
+#[derive(Component)]
+pub struct FetchModusButton(pub usize);
+
+/// The item whose card was last clicked on the inventory screen.
+#[derive(Resource, Default)]
+pub struct SelectedInventoryItem(pub Option<Entity>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InventoryItemAction {
+	Use,
+	/// Take it out and hold it, so it can be thrown
+	Hold,
+	/// Take it out and put it down in front of the player
+	Drop,
+	/// Leave it in, but let `PlayerAction::UseQuickItem` use it from outside the sylladex
+	QuickSlot,
+}
+
+impl InventoryItemAction {
+	const ALL: [Self; 4] = [Self::Use, Self::Hold, Self::Drop, Self::QuickSlot];
+
+	fn label(self) -> &'static str {
+		match self {
+			InventoryItemAction::Use => "Use",
+			InventoryItemAction::Hold => "Hold",
+			InventoryItemAction::Drop => "Drop",
+			InventoryItemAction::QuickSlot => "Quick slot [R]",
+		}
+	}
+}
+
+/// Does something with the selected item when clicked.
+#[derive(Component)]
+pub struct InventoryItemActionButton(pub InventoryItemAction);
+
+/// The item being dragged onto another card, if any.
+#[derive(Resource, Default)]
+pub struct DraggedInventoryItem(pub Option<Entity>);
+
+/// When set, only items with this tag stand out on the inventory screen.
+#[derive(Resource, Default)]
+pub struct InventoryFilter(pub Option<String>);
+
+impl InventoryFilter {
+	fn allows(&self, definition: Option<&ItemDefinition>) -> bool {
+		match &self.0 {
+			Some(tag) => definition.is_some_and(|definition| definition.tags.contains(tag)),
+			None => true,
+		}
+	}
+}
+
+/// Sets the `InventoryFilter` when clicked.
+#[derive(Component)]
+pub struct InventoryFilterButton(pub Option<String>);
+
+/// Ways to sort the cards, for modi that let the player rearrange them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InventorySort {
+	Name,
+	/// By each item's first tag, then by name
+	Tag,
+}
+
+impl InventorySort {
+	const ALL: [Self; 2] = [Self::Name, Self::Tag];
+
+	fn label(self) -> &'static str {
+		match self {
+			InventorySort::Name => "Sort by name",
+			InventorySort::Tag => "Sort by tag",
+		}
+	}
+}
+
+#[derive(Component)]
+pub struct InventorySortButton(pub InventorySort);
+
+/// Follows the cursor around, describing whatever card it's over.
+#[derive(Component)]
+pub struct InventoryTooltip;
+
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Reflect, Debug)]
+pub enum InventoryScreenAction {
+	SelectPrevious,
+	SelectNext,
+	UseSelected,
+}
+impl Actionlike for InventoryScreenAction {
+	fn input_control_kind(&self) -> InputControlKind {
+		match self {
+			InventoryScreenAction::SelectPrevious => InputControlKind::Button,
+			InventoryScreenAction::SelectNext => InputControlKind::Button,
+			InventoryScreenAction::UseSelected => InputControlKind::Button,
+		}
+	}
+}
+
 pub fn spawn_inventory_screen(mut commands: Commands) {
+	commands.spawn((
+		Name::new("Inventory Screen"),
+		NodeBundle {
+			style: Style {
+				width: Val::Percent(100.0),
+				height: Val::Percent(100.0),
+				padding: UiRect::all(Val::Px(10.0)),
+				row_gap: Val::Px(10.0),
+				flex_direction: FlexDirection::Column,
+				..default()
+			},
+			background_color: bevy::color::palettes::css::GRAY.with_alpha(0.5).into(),
+			visibility: Visibility::Hidden,
+			..default()
+		},
+		input_manager(
+			InputMap::default().with(MenuAction::CloseMenu, KeyCode::KeyV),
+			false,
+		),
+		input_manager(
+			InputMap::default()
+				.with(InventoryScreenAction::SelectPrevious, KeyCode::ArrowLeft)
+				.with(InventoryScreenAction::SelectPrevious, KeyCode::ArrowUp)
+				.with(InventoryScreenAction::SelectNext, KeyCode::ArrowRight)
+				.with(InventoryScreenAction::SelectNext, KeyCode::ArrowDown)
+				.with(InventoryScreenAction::UseSelected, KeyCode::Enter),
+			false,
+		),
+		PlayerCameraNode,
+		Menu,
+		MenuWithMouse,
+		MenuWithInputManager,
+		MenuHidesWhenClosed,
+		InventoryScreen,
+	));
+
+	commands.spawn((
+		Name::new("Inventory Tooltip"),
+		TextBundle {
+			style: Style {
+				position_type: PositionType::Absolute,
+				padding: UiRect::all(Val::Px(5.0)),
+				..default()
+			},
+			background_color: css::BLACK.with_alpha(0.8).into(),
+			visibility: Visibility::Hidden,
+			z_index: ZIndex::Global(1),
+			..default()
+		},
+		PlayerCameraNode,
+		InventoryTooltip,
+	));
+}
+
+/// Lays the sylladex's cards out the way its modus arranges them.
+pub fn update_inventory_screen(
+	mut commands: Commands,
+	inventories: Query<Ref<Inventory>>,
+	items: Query<&Item>,
+	inventory_screen: Query<Entity, With<InventoryScreen>>,
+	mut selection: ResMut<SelectedInventoryItem>,
+	filter: Res<InventoryFilter>,
+	definitions: Res<Assets<ItemDefinition>>,
+) {
+	let inventory = ok_or_return!(inventories.get_single());
+	if !inventory.is_changed() && !selection.is_changed() && !filter.is_changed() {
+		return;
+	}
+	let inventory = inventory.into_inner();
+	let inventory_screen = inventory_screen.single();
+	let modus = inventory.modus();
+	let face_down = modus.layout() == ModusLayout::FaceDown;
+
+	// Whatever was selected might not be in the sylladex anymore
+	if selection
+		.0
+		.is_some_and(|item| inventory.card(item).is_none())
+	{
+		selection.0 = None;
+	}
+
+	// Every tag in the sylladex, unless the modus is keeping them secret
+	let mut tags: Vec<String> = Vec::new();
+	if !face_down {
+		let definitions_in_inventory = inventory
+			.items()
+			.filter_map(|item| items.get(item).ok())
+			.filter_map(|item| definitions.get(&item.definition));
+		for definition in definitions_in_inventory {
+			for tag in &definition.tags {
+				if !tags.contains(tag) {
+					tags.push(tag.clone());
+				}
+			}
+		}
+		// Keep the filter's own button around even after its last item leaves
+		if let Some(tag) = &filter.0 {
+			if !tags.contains(tag) {
+				tags.push(tag.clone());
+			}
+		}
+		tags.sort_unstable();
+	}
+
+	let cards = CardContext {
+		inventory,
+		items: &items,
+		definitions: &definitions,
+		selection: &selection,
+		filter: &filter,
+	};
+
 	commands
+		.entity(inventory_screen)
+		.despawn_descendants()
+		.with_children(|parent| {
+			parent
+				.spawn(NodeBundle {
+					style: Style {
+						column_gap: Val::Px(CARD_GAP),
+						..default()
+					},
+					..default()
+				})
+				.with_children(|parent| {
+					for (index, other_modus) in FETCH_MODI.iter().enumerate() {
+						let color = if other_modus.name() == modus.name() {
+							css::GOLDENROD
+						} else {
+							css::DARK_GRAY
+						};
+						spawn_button(parent, other_modus.name(), color, FetchModusButton(index));
+					}
+				});
+
+			let (weight_label, weight_color) = if inventory.is_encumbered() {
+				(" (encumbered)", css::ORANGE_RED)
+			} else {
+				("", css::WHITE)
+			};
+			parent.spawn(TextBundle::from_section(
+				format!(
+					"Carrying {:.1} / {MAX_WEIGHT:.1}{weight_label}",
+					inventory.weight()
+				),
+				TextStyle {
+					color: weight_color.into(),
+					..default()
+				},
+			));
+
+			if modus.can_rearrange() || !tags.is_empty() {
+				parent
+					.spawn(NodeBundle {
+						style: Style {
+							column_gap: Val::Px(CARD_GAP),
+							row_gap: Val::Px(CARD_GAP),
+							flex_wrap: FlexWrap::Wrap,
+							..default()
+						},
+						..default()
+					})
+					.with_children(|parent| {
+						if modus.can_rearrange() {
+							for sort in InventorySort::ALL {
+								spawn_button(
+									parent,
+									sort.label(),
+									css::DARK_GRAY,
+									InventorySortButton(sort),
+								);
+							}
+						}
+						if !tags.is_empty() {
+							let color = |tag: Option<&String>| {
+								if filter.0.as_ref() == tag {
+									css::GOLDENROD
+								} else {
+									css::DARK_GRAY
+								}
+							};
+							spawn_button(parent, "All", color(None), InventoryFilterButton(None));
+							for tag in &tags {
+								spawn_button(
+									parent,
+									tag,
+									color(Some(tag)),
+									InventoryFilterButton(Some(tag.clone())),
+								);
+							}
+						}
+					});
+			}
+
+			let num_cards = inventory.cards().len();
+			if modus.layout() == ModusLayout::Tree {
+				parent
+					.spawn(NodeBundle {
+						style: Style {
+							width: Val::Percent(100.0),
+							flex_direction: FlexDirection::Column,
+							row_gap: Val::Px(CARD_GAP),
+							..default()
+						},
+						..default()
+					})
+					.with_children(|parent| {
+						let mut level_start = 0;
+						let mut level_width = 1;
+						while level_start < num_cards {
+							parent
+								.spawn(NodeBundle {
+									style: Style {
+										width: Val::Percent(100.0),
+										justify_content: JustifyContent::SpaceAround,
+										..default()
+									},
+									..default()
+								})
+								.with_children(|parent| {
+									for index in level_start..level_start + level_width {
+										if index < num_cards {
+											cards.spawn_card(parent, index);
+										} else {
+											// Keeps the cards that do exist under their parents
+											parent.spawn(NodeBundle {
+												style: Style {
+													width: Val::Px(CARD_SIZE),
+													height: Val::Px(CARD_SIZE),
+													..default()
+												},
+												..default()
+											});
+										}
+									}
+								});
+							level_start += level_width;
+							level_width *= 2;
+						}
+					});
+			} else {
+				let (flex_direction, flex_wrap) = match modus.layout() {
+					ModusLayout::Column => (FlexDirection::Column, FlexWrap::NoWrap),
+					ModusLayout::Row => (FlexDirection::Row, FlexWrap::NoWrap),
+					_ => (FlexDirection::Row, FlexWrap::Wrap),
+				};
+				parent
+					.spawn(NodeBundle {
+						style: Style {
+							flex_direction,
+							flex_wrap,
+							row_gap: Val::Px(CARD_GAP),
+							column_gap: Val::Px(CARD_GAP),
+							..default()
+						},
+						..default()
+					})
+					.with_children(|parent| {
+						for index in 0..num_cards {
+							cards.spawn_card(parent, index);
+						}
+					});
+			}
+
+			// Face down cards stay a mystery even when they're selected
+			let description = selection
+				.0
+				.filter(|_| !face_down)
+				.and_then(|item| describe_item(inventory, item, &items, &definitions));
+			if let Some(description) = description {
+				parent.spawn(TextBundle::from_section(description, TextStyle::default()));
+			}
+
+			if selection.0.is_some() {
+				parent
+					.spawn(NodeBundle {
+						style: Style {
+							column_gap: Val::Px(CARD_GAP),
+							..default()
+						},
+						..default()
+					})
+					.with_children(|parent| {
+						for action in InventoryItemAction::ALL {
+							spawn_button(
+								parent,
+								action.label(),
+								css::DARK_GRAY,
+								InventoryItemActionButton(action),
+							);
+						}
+					});
+			}
+		});
+}
+
+fn spawn_button(parent: &mut ChildBuilder, label: &str, color: Srgba, marker: impl Bundle) {
+	parent
 		.spawn((
-			NodeBundle {
+			ButtonBundle {
 				style: Style {
-					width: Val::Percent(100.0),
-					height: Val::Percent(100.0),
-					margin: UiRect::all(Val::Px(10.0)),
-					row_gap: Val::Px(10.0),
-					column_gap: Val::Px(10.0),
-					flex_wrap: FlexWrap::Wrap,
+					padding: UiRect::all(Val::Px(10.0)),
 					..default()
 				},
-				background_color: bevy::color::palettes::css::GRAY.with_alpha(0.5).into(),
-				visibility: Visibility::Hidden,
+				background_color: color.into(),
 				..default()
 			},
-			input_manager_bundle(
-				InputMap::default().with(MenuAction::CloseMenu, KeyCode::KeyV),
-				false,
-			),
-			PlayerCameraNode,
-			Menu,
-			MenuWithMouse,
-			MenuWithInputManager,
-			MenuHidesWhenClosed,
-			InventoryScreen,
+			marker,
 		))
-		.insert(Name::new("Inventory Screen"));
+		.with_children(|parent| {
+			parent.spawn(TextBundle::from_section(label, TextStyle::default()));
+		});
 }
 
-pub fn add_item_to_inventory_screen(
-	mut ev_picked_up: EventReader<ItemPickedUp>,
-	mut commands: Commands,
-	items: Query<&Item>,
-	inventory_screen: Query<Entity, With<InventoryScreen>>,
-) {
-	let inventory_screen = inventory_screen.single();
+/// Everything worth knowing about an item in the sylladex, for tooltips and the selected item's details.
+fn describe_item(
+	inventory: &Inventory,
+	item: Entity,
+	items: &Query<&Item>,
+	definitions: &Assets<ItemDefinition>,
+) -> Option<String> {
+	let card = inventory.card(item)?;
+	let item = items.get(item).ok()?;
+	let definition = definitions.get(&item.definition)?;
 
-	for ItemPickedUp(item_entity) in ev_picked_up.read() {
-		let item = items.get(*item_entity).expect("Item not found");
+	let mut lines = vec![
+		format!("{} [{}]", card.name, item.code),
+		format!(
+			"Size {:.2}, weight {:.2}",
+			definition.size, definition.weight
+		),
+	];
+	if !definition.tags.is_empty() {
+		lines.push(definition.tags.join(", "));
+	}
+	if definition.weapon.is_some() {
+		lines.push("Can be equipped as a weapon".to_owned());
+	}
+	if definition.upgrade_material {
+		lines.push("Can be spent on weapon upgrades".to_owned());
+	}
+	if let Some(consumable) = &definition.consumable {
+		if consumable.restore > 0.0 {
+			lines.push(format!("Restores {:.1} health", consumable.restore));
+		}
+		if consumable.healing > 0.0 {
+			lines.push(format!(
+				"Heals {:.1} per second for {:.0} seconds",
+				consumable.healing, consumable.healing_duration
+			));
+		}
+	}
+	if let Some(throw_damage) = &definition.throw_damage {
+		lines.push(format!(
+			"Deals {:.1} {:?} damage when thrown",
+			throw_damage.damage, throw_damage.damage_type
+		));
+	}
+	Some(lines.join("\n"))
+}
+
+/// What every card on the inventory screen needs to know to draw itself.
+struct CardContext<'a, 'w, 's> {
+	inventory: &'a Inventory,
+	items: &'a Query<'w, 's, &'static Item>,
+	definitions: &'a Assets<ItemDefinition>,
+	selection: &'a SelectedInventoryItem,
+	filter: &'a InventoryFilter,
+}
+
+impl CardContext<'_, '_, '_> {
+	fn spawn_card(&self, parent: &mut ChildBuilder, index: usize) {
+		let modus = self.inventory.modus();
+		let face_down = modus.layout() == ModusLayout::FaceDown;
+		let card = self.inventory.cards()[index].as_ref();
+		let item = card.and_then(|card| self.items.get(card.item).ok());
+		let definition = item.and_then(|item| self.definitions.get(&item.definition));
+		let filtered_out = card.is_some() && !face_down && !self.filter.allows(definition);
 
-		commands
-			.spawn(ImageBundle {
-				image: item.icon.clone().into(),
+		let icon = item.filter(|_| !face_down).map(|item| {
+			let mut icon = UiImage::from(item.icon.clone());
+			if filtered_out {
+				icon.color = Color::WHITE.with_alpha(0.2);
+			}
+			icon
+		});
+		let border_color = if card.is_some_and(|card| self.selection.0 == Some(card.item)) {
+			css::WHITE
+		} else if modus.can_retrieve(self.inventory.cards(), index) {
+			css::GOLDENROD
+		} else {
+			css::BLACK
+		};
+		let background_color = if card.is_some() && !filtered_out {
+			css::DARK_GRAY
+		} else {
+			css::DARK_GRAY.with_alpha(0.3)
+		};
+
+		let mut button = parent.spawn((
+			ButtonBundle {
+				image: icon.unwrap_or_default(),
 				style: Style {
-					width: Val::Px(100.0),
-					height: Val::Px(100.0),
+					width: Val::Px(CARD_SIZE),
+					height: Val::Px(CARD_SIZE),
+					border: UiRect::all(Val::Px(CARD_BORDER)),
 					..default()
 				},
-				background_color: css::DARK_GRAY.into(),
+				border_color: border_color.into(),
+				background_color: background_color.into(),
 				..default()
-			})
-			.set_parent(inventory_screen);
+			},
+			InventoryScreenCard(index),
+		));
+		if let Some(card) = card {
+			button.insert(InventoryScreenItem(card.item));
+		}
+	}
+}
+
+pub fn click_inventory_items(
+	items: Query<(&InventoryScreenItem, &Interaction), Changed<Interaction>>,
+	mut selection: ResMut<SelectedInventoryItem>,
+	mut dragged: ResMut<DraggedInventoryItem>,
+) {
+	for (InventoryScreenItem(item), interaction) in items.iter() {
+		if *interaction == Interaction::Pressed {
+			selection.0 = Some(*item);
+			dragged.0 = Some(*item);
+		}
+	}
+}
+
+/// Moves the dragged item to whichever card the mouse lets go over, if the modus allows it.
+pub fn drop_dragged_items(
+	mouse: Res<ButtonInput<MouseButton>>,
+	cards: Query<(&InventoryScreenCard, &Interaction)>,
+	mut dragged: ResMut<DraggedInventoryItem>,
+	mut inventories: Query<&mut Inventory>,
+) {
+	if !mouse.just_released(MouseButton::Left) {
+		return;
+	}
+	let item = some_or_return!(dragged.0.take());
+	let Some((InventoryScreenCard(index), _)) = cards
+		.iter()
+		.find(|(_, interaction)| **interaction == Interaction::Hovered)
+	else {
+		return;
+	};
+
+	let mut inventory = ok_or_return!(inventories.get_single_mut());
+	if inventory.card_index(item) != Some(*index) {
+		inventory.move_item(item, *index);
+	}
+}
+
+pub fn update_inventory_tooltip(
+	cards: Query<(&Interaction, Option<&InventoryScreenItem>), With<InventoryScreenCard>>,
+	inventories: Query<&Inventory>,
+	items: Query<&Item>,
+	definitions: Res<Assets<ItemDefinition>>,
+	dragged: Res<DraggedInventoryItem>,
+	window: Query<&Window, With<PrimaryWindow>>,
+	mut tooltip: Query<(&mut Text, &mut Style, &mut Visibility), With<InventoryTooltip>>,
+) {
+	let (mut text, mut style, mut visibility) = ok_or_return!(tooltip.get_single_mut());
+	let inventory = ok_or_return!(inventories.get_single());
+
+	// Nothing on the screen gets hovered while it's closed
+	let hovered = cards
+		.iter()
+		.find(|(interaction, _)| **interaction != Interaction::None)
+		.map(|(_, item)| item.map(|InventoryScreenItem(item)| *item));
+	let description = match (hovered, dragged.0) {
+		(Some(_), Some(item)) => inventory
+			.card(item)
+			.map(|card| format!("Moving {}", card.name)),
+		(Some(Some(item)), None) if inventory.modus().layout() != ModusLayout::FaceDown => {
+			describe_item(inventory, item, &items, &definitions)
+		}
+		_ => None,
+	};
+	let cursor = window.get_single().ok().and_then(Window::cursor_position);
+
+	let (Some(description), Some(cursor)) = (description, cursor) else {
+		visibility.set_if_neq(Visibility::Hidden);
+		return;
+	};
+	if text.sections.first().map(|section| &section.value) != Some(&description) {
+		text.sections = vec![TextSection::new(description, TextStyle::default())];
+	}
+	style.left = Val::Px(cursor.x + TOOLTIP_OFFSET);
+	style.top = Val::Px(cursor.y + TOOLTIP_OFFSET);
+	visibility.set_if_neq(Visibility::Inherited);
+}
+
+/// Steps the selection through the cards that pass the filter, and uses the selected item.
+pub fn select_with_keys(
+	input: Query<&ActionState<InventoryScreenAction>>,
+	inventories: Query<&Inventory>,
+	items: Query<&Item>,
+	definitions: Res<Assets<ItemDefinition>>,
+	filter: Res<InventoryFilter>,
+	mut selection: ResMut<SelectedInventoryItem>,
+	mut ev_used: EventWriter<ItemUsed>,
+) {
+	let input = some_or_return!(input.iter().find(|input| !input.disabled()));
+	let inventory = ok_or_return!(inventories.get_single());
+
+	if input.just_pressed(&InventoryScreenAction::UseSelected) {
+		if let Some(item) = selection.0 {
+			ev_used.send(ItemUsed(item));
+		}
+		return;
+	}
+	let step = if input.just_pressed(&InventoryScreenAction::SelectNext) {
+		1
+	} else if input.just_pressed(&InventoryScreenAction::SelectPrevious) {
+		-1
+	} else {
+		return;
+	};
+
+	let face_down = inventory.modus().layout() == ModusLayout::FaceDown;
+	let selectable: Vec<Entity> = inventory
+		.items()
+		.filter(|&item| {
+			face_down
+				|| filter.allows(
+					items
+						.get(item)
+						.ok()
+						.and_then(|item| definitions.get(&item.definition)),
+				)
+		})
+		.collect();
+	if selectable.is_empty() {
+		return;
 	}
+
+	let current = selection
+		.0
+		.and_then(|item| selectable.iter().position(|&other| other == item));
+	let next = match current {
+		Some(index) => (index as isize + step).rem_euclid(selectable.len() as isize) as usize,
+		None if step > 0 => 0,
+		None => selectable.len() - 1,
+	};
+	selection.0 = Some(selectable[next]);
+}
+
+pub fn sort_inventory(
+	buttons: Query<(&InventorySortButton, &Interaction), Changed<Interaction>>,
+	mut inventories: Query<&mut Inventory>,
+	items: Query<&Item>,
+	definitions: Res<Assets<ItemDefinition>>,
+) {
+	for (InventorySortButton(sort), interaction) in buttons.iter() {
+		if *interaction != Interaction::Pressed {
+			continue;
+		}
+
+		let mut inventory = ok_or_return!(inventories.get_single_mut());
+		match sort {
+			InventorySort::Name => inventory.sort_cards(|card| card.name.clone()),
+			InventorySort::Tag => inventory.sort_cards(|card| {
+				let tag = items
+					.get(card.item)
+					.ok()
+					.and_then(|item| definitions.get(&item.definition))
+					.and_then(|definition| definition.tags.first().cloned());
+				// Untagged items go after everything else
+				(tag.is_none(), tag, card.name.clone())
+			}),
+		};
+	}
+}
+
+pub fn filter_inventory(
+	buttons: Query<(&InventoryFilterButton, &Interaction), Changed<Interaction>>,
+	mut filter: ResMut<InventoryFilter>,
+) {
+	for (InventoryFilterButton(tag), interaction) in buttons.iter() {
+		if *interaction == Interaction::Pressed {
+			filter.0 = tag.clone();
+		}
+	}
+}
+
+pub fn act_on_selected_item(
+	mut commands: Commands,
+	buttons: Query<(&InventoryItemActionButton, &Interaction), Changed<Interaction>>,
+	mut selection: ResMut<SelectedInventoryItem>,
+	mut inventories: Query<&mut Inventory>,
+	mut transforms: Query<&mut Transform, Without<Inventory>>,
+	held_items: Query<Entity, With<HeldItem>>,
+	player_camera: Query<&GlobalTransform, With<PlayerCamera>>,
+	mut ev_used: EventWriter<ItemUsed>,
+	mut ev_retrieved: EventWriter<ItemRetrieved>,
+	mut ev_dropped: EventWriter<ItemDropped>,
+	mut quick_item: ResMut<QuickItem>,
+) {
+	for (InventoryItemActionButton(action), interaction) in buttons.iter() {
+		if *interaction != Interaction::Pressed {
+			continue;
+		}
+		let Some(item) = selection.0 else {
+			continue;
+		};
+
+		let mut inventory = ok_or_return!(inventories.get_single_mut());
+		match action {
+			InventoryItemAction::Use => {
+				ev_used.send(ItemUsed(item));
+			}
+			InventoryItemAction::QuickSlot => {
+				quick_item.0 = Some(item);
+			}
+			InventoryItemAction::Hold => {
+				if !inventory.retrieve(item) {
+					continue;
+				}
+				selection.0 = None;
+
+				hold_item(&mut commands, &held_items, &mut ev_dropped, item);
+				ev_retrieved.send(ItemRetrieved(item));
+			}
+			InventoryItemAction::Drop => {
+				if !inventory.retrieve(item) {
+					continue;
+				}
+				selection.0 = None;
+
+				let player_camera = player_camera.get_single().expect("Player camera not found");
+				if let Ok(mut transform) = transforms.get_mut(item) {
+					transform.translation =
+						player_camera.translation() + player_camera.forward() * DROP_DISTANCE;
+				}
+				release_item(&mut commands, item);
+				ev_dropped.send(ItemDropped(item));
+			}
+		}
+	}
+}
+
+pub fn switch_fetch_modus(
+	mut commands: Commands,
+	buttons: Query<(&FetchModusButton, &Interaction), Changed<Interaction>>,
+	mut inventories: Query<(&mut Inventory, &GlobalTransform)>,
+	mut transforms: Query<&mut Transform, Without<Inventory>>,
+) {
+	for (FetchModusButton(index), interaction) in buttons.iter() {
+		if *interaction != Interaction::Pressed {
+			continue;
+		}
+
+		for (mut inventory, body_transform) in inventories.iter_mut() {
+			for item in inventory.set_modus(FETCH_MODI[*index]) {
+				eject_item(
+					&mut commands,
+					&mut transforms,
+					item,
+					body_transform.translation(),
+				);
+			}
+		}
+	}
+}
+
+/// A message that says why the last pickup didn't work.
+#[derive(Component)]
+pub struct PickupFailureMessage;
+
+pub fn show_pickup_failures(
+	mut commands: Commands,
+	mut ev_pickup_failed: EventReader<ItemPickupFailed>,
+	inventories: Query<&Inventory>,
+	messages: Query<Entity, With<PickupFailureMessage>>,
+) {
+	let Some(ev) = ev_pickup_failed.read().last() else {
+		return;
+	};
+	let modus = ok_or_return!(inventories.get_single()).modus();
+	let message = match ev.reason {
+		CaptchalogueError::NoRoom => {
+			format!("No room in your {} modus for {}", modus.name(), ev.name)
+		}
+		CaptchalogueError::TooHeavy => format!("{} is too heavy to carry", ev.name),
+	};
+
+	for message in messages.iter() {
+		commands.entity(message).despawn_recursive();
+	}
+	commands.spawn((
+		Name::new("Pickup Failure Message"),
+		TextBundle::from_section(
+			message,
+			TextStyle {
+				color: css::ORANGE_RED.into(),
+				..default()
+			},
+		)
+		.with_text_justify(JustifyText::Center)
+		.with_style(Style {
+			position_type: PositionType::Absolute,
+			width: Val::Percent(100.0),
+			top: Val::Percent(65.0),
+			..default()
+		}),
+		PlayerCameraNode,
+		DespawnTimer::new(PICKUP_FAILURE_DURATION),
+		PickupFailureMessage,
+	));
 }
@@ -0,0 +1,159 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::entity::{DamageType, EntityDamaged, GelViscosity, Healing};
+use crate::player_controller::PlayerBody;
+use crate::{ok_or_continue, ok_or_return, some_or_continue, some_or_return};
+
+use super::{Inventory, Item, ItemDefinition, ItemUsed};
+
+/// What using an item up does for the player.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Consumable {
+	/// Health restored straight away
+	#[serde(default)]
+	pub restore: f32,
+	/// Health restored per second, for `healing_duration` seconds
+	#[serde(default)]
+	pub healing: f32,
+	#[serde(default)]
+	pub healing_duration: f32,
+}
+
+/// How hard an item hits when it's thrown into something.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ThrowDamage {
+	pub damage: f32,
+	#[serde(default)]
+	pub damage_type: DamageType,
+}
+
+/// `Healing` from a consumable, which wears off.
+#[derive(Component)]
+pub struct ItemHealing(Timer);
+
+/// A thrown item that hasn't hit anything yet.
+#[derive(Component)]
+pub struct ThrownItem {
+	damage: ThrowDamage,
+	/// How far the item reaches from its middle, so it hits things when its edge does
+	reach: f32,
+	last_position: Vec3,
+}
+
+impl ThrownItem {
+	pub fn new(definition: &ItemDefinition, position: Vec3) -> Option<Self> {
+		Some(Self {
+			damage: definition.throw_damage.clone()?,
+			reach: definition.size * 0.5,
+			last_position: position,
+		})
+	}
+}
+
+/// The item `PlayerAction::UseQuickItem` uses without opening the sylladex.
+#[derive(Resource, Default)]
+pub struct QuickItem(pub Option<Entity>);
+
+/// Uses up consumable items the player uses from their sylladex. Ones that are also weapons get equipped instead.
+pub fn consume_items(
+	mut commands: Commands,
+	mut ev_used: EventReader<ItemUsed>,
+	items: Query<&Item>,
+	definitions: Res<Assets<ItemDefinition>>,
+	mut players: Query<(Entity, &mut Inventory, &mut GelViscosity), With<PlayerBody>>,
+) {
+	for ItemUsed(item) in ev_used.read() {
+		let definition = ok_or_continue!(items.get(*item)).definition.clone();
+		let definition = some_or_continue!(definitions.get(&definition));
+		let consumable = some_or_continue!(&definition.consumable);
+		if definition.weapon.is_some() {
+			continue;
+		}
+		let (player, mut inventory, mut health) = ok_or_continue!(players.get_single_mut());
+
+		health.value = (health.value + consumable.restore).min(health.max);
+		if consumable.healing > 0.0 && consumable.healing_duration > 0.0 {
+			commands.entity(player).insert((
+				Healing(consumable.healing),
+				ItemHealing(Timer::from_seconds(
+					consumable.healing_duration,
+					TimerMode::Once,
+				)),
+			));
+		}
+
+		inventory.take(*item);
+		commands.entity(*item).despawn_recursive();
+	}
+}
+
+pub fn wear_off_item_healing(
+	mut commands: Commands,
+	mut healings: Query<(Entity, &mut ItemHealing)>,
+	time: Res<Time>,
+) {
+	for (entity, mut healing) in healings.iter_mut() {
+		healing.0.tick(time.delta());
+		if healing.0.finished() {
+			commands.entity(entity).remove::<(Healing, ItemHealing)>();
+		}
+	}
+}
+
+/// Damages whatever a thrown item runs into first. Anything without health, like the ground, just stops it counting as thrown.
+pub fn hit_with_thrown_items(
+	mut commands: Commands,
+	mut thrown_items: Query<(Entity, &mut ThrownItem, &GlobalTransform)>,
+	damageable: Query<(), With<GelViscosity>>,
+	players: Query<(), With<PlayerBody>>,
+	rapier_context: Res<RapierContext>,
+	mut ev_hit: EventWriter<EntityDamaged>,
+) {
+	for (item, mut thrown, transform) in thrown_items.iter_mut() {
+		let start = thrown.last_position;
+		let delta = transform.translation() - start;
+		thrown.last_position = transform.translation();
+		let direction = some_or_continue!(delta.try_normalize());
+
+		let hit = rapier_context.cast_ray(
+			start,
+			direction,
+			delta.length() + thrown.reach,
+			true,
+			QueryFilter::new()
+				.exclude_sensors()
+				.predicate(&|entity| entity != item && !players.contains(entity)),
+		);
+		let Some((victim, _)) = hit else {
+			continue;
+		};
+
+		if damageable.contains(victim) {
+			ev_hit.send(EntityDamaged {
+				victim,
+				damage: thrown.damage.damage,
+				fray_modifier: 1.0,
+				damage_type: thrown.damage.damage_type,
+				effects: Vec::new(),
+				knockback: direction,
+			});
+		}
+		commands.entity(item).remove::<ThrownItem>();
+	}
+}
+
+pub fn use_quick_item(
+	inventories: Query<&Inventory, With<PlayerBody>>,
+	quick_item: Res<QuickItem>,
+	mut ev_used: EventWriter<ItemUsed>,
+) {
+	let item = some_or_return!(quick_item.0);
+	let inventory = ok_or_return!(inventories.get_single());
+
+	// Whatever was in the slot might have been used up or dropped since
+	if inventory.card(item).is_some() {
+		ev_used.send(ItemUsed(item));
+	}
+}
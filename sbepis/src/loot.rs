@@ -0,0 +1,181 @@
+use bevy::prelude::*;
+use bevy_common_assets::ron::RonAssetPlugin;
+use bevy_rapier3d::prelude::Velocity;
+use rand::distributions::{Distribution, WeightedIndex};
+use serde::Deserialize;
+
+use crate::alchemy::{spawn_grist, GristAmounts, GristAssets};
+use crate::entity::EntityKilled;
+use crate::inventory::{spawn_item, ItemDefinition};
+use crate::questing::{QuestItem, QuestType, Quests};
+use crate::{gridbox_material, ok_or_continue, some_or_continue, BoxBundle};
+
+/// How fast drops get flung out of whatever dropped them
+const DROP_SPEED: f32 = 3.0;
+const DROP_SPIN: f32 = 4.0;
+
+pub struct LootPlugin;
+impl Plugin for LootPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_plugins(RonAssetPlugin::<LootTable>::new(&["loot.ron"]))
+			.add_systems(Update, drop_loot);
+	}
+}
+
+/// What a kind of enemy can drop when it's killed, loaded from a `.loot.ron` file.
+#[derive(Asset, Deserialize, TypePath)]
+pub struct LootTable {
+	/// How many times to pick from the entries
+	pub rolls: u32,
+	pub entries: Vec<LootEntry>,
+	/// Always dropped while there are fewer quest items around than fetch quests. Needs to be an item to be picked up.
+	#[serde(default)]
+	pub quest_drop: Option<LootDrop>,
+	/// Always dropped, on top of whatever the rolls pick
+	#[serde(default)]
+	pub grist: GristAmounts,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LootEntry {
+	pub weight: f32,
+	/// `None` drops nothing, which makes the other entries rarer
+	#[serde(default)]
+	pub drop: Option<LootDrop>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum LootDrop {
+	/// Path to the `.item.ron` of an item that can be picked up
+	Item(String),
+	/// A box that just bounces around
+	Debris { size: f32, material: String },
+}
+
+/// Rolls on this loot table when killed.
+#[derive(Component)]
+pub struct LootDropper(pub Handle<LootTable>);
+
+/// Flings the drop out from `position`. Items whose definitions haven't loaded yet don't drop.
+fn spawn_drop(
+	commands: &mut Commands,
+	meshes: &mut Assets<Mesh>,
+	materials: &mut Assets<StandardMaterial>,
+	asset_server: &AssetServer,
+	definitions: &Assets<ItemDefinition>,
+	drop: &LootDrop,
+	position: Vec3,
+	up: Vec3,
+) -> Option<Entity> {
+	let drop_entity = match drop {
+		LootDrop::Item(path) => {
+			let handle = asset_server.load(path.clone());
+			let definition = definitions.get(&handle)?;
+			spawn_item(
+				commands,
+				asset_server,
+				meshes,
+				materials,
+				definition,
+				handle,
+				position,
+			)
+		}
+		LootDrop::Debris { size, material } => commands
+			.spawn((
+				Name::new("Debris"),
+				BoxBundle::new(
+					position,
+					meshes.add(Cuboid::from_size(Vec3::splat(*size))),
+					gridbox_material(material, materials, asset_server),
+				)
+				.with_collider_size(size * 0.5),
+			))
+			.id(),
+	};
+
+	fling(commands, drop_entity, up);
+	Some(drop_entity)
+}
+
+/// Sends a drop flying off in a random direction, mostly upwards.
+fn fling(commands: &mut Commands, entity: Entity, up: Vec3) {
+	let scatter = rand::random::<Vec3>() * 2.0 - Vec3::ONE;
+	commands.entity(entity).insert(Velocity {
+		linvel: (scatter.reject_from(up) + up * 2.0).normalize_or_zero() * DROP_SPEED,
+		angvel: scatter * DROP_SPIN,
+	});
+}
+
+fn drop_loot(
+	mut ev_killed: EventReader<EntityKilled>,
+	mut commands: Commands,
+	droppers: Query<(&LootDropper, &Transform)>,
+	loot_tables: Res<Assets<LootTable>>,
+	quests: Res<Quests>,
+	quest_items: Query<(), With<QuestItem>>,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+	asset_server: Res<AssetServer>,
+	definitions: Res<Assets<ItemDefinition>>,
+	grist_assets: Res<GristAssets>,
+) {
+	let num_fetch_quests = quests
+		.0
+		.values()
+		.filter(|quest| matches!(quest.quest_type, QuestType::Fetch { .. }))
+		.count();
+	let mut num_quest_items = quest_items.iter().count();
+	let mut rng = rand::thread_rng();
+
+	for EntityKilled { entity } in ev_killed.read() {
+		let (dropper, transform) = ok_or_continue!(droppers.get(*entity));
+		let loot_table = some_or_continue!(loot_tables.get(&dropper.0));
+		let up = transform.up().into();
+		let position = transform.translation + up * 0.2;
+
+		if let Some(quest_drop) = loot_table
+			.quest_drop
+			.as_ref()
+			.filter(|_| num_quest_items < num_fetch_quests)
+		{
+			if let Some(quest_item) = spawn_drop(
+				&mut commands,
+				&mut meshes,
+				&mut materials,
+				&asset_server,
+				&definitions,
+				quest_drop,
+				position,
+				up,
+			) {
+				commands.entity(quest_item).insert(QuestItem);
+				num_quest_items += 1;
+			}
+		}
+
+		for (grist_type, amount) in loot_table.grist.0.iter() {
+			let grist = spawn_grist(&mut commands, &grist_assets, *grist_type, *amount, position);
+			fling(&mut commands, grist, up);
+		}
+
+		let Ok(weights) = WeightedIndex::new(loot_table.entries.iter().map(|entry| entry.weight))
+		else {
+			continue;
+		};
+		for _ in 0..loot_table.rolls {
+			if let Some(drop) = &loot_table.entries[weights.sample(&mut rng)].drop {
+				spawn_drop(
+					&mut commands,
+					&mut meshes,
+					&mut materials,
+					&asset_server,
+					&definitions,
+					drop,
+					position,
+					up,
+				);
+			}
+		}
+	}
+}
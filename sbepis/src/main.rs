@@ -11,13 +11,16 @@ use winit::window::Icon;
 
 use self::main_bundles::*;
 
+mod alchemy;
 mod camera;
+mod debug_draw;
 mod entity;
 mod fray;
 mod gravity;
 pub mod input;
 mod inventory;
 pub mod iter_system;
+mod loot;
 mod main_bundles;
 pub mod menus;
 mod npcs;
@@ -26,6 +29,7 @@ mod overview_camera;
 mod player_commands;
 mod player_controller;
 mod questing;
+mod save;
 mod skybox;
 pub mod util;
 
@@ -77,6 +81,10 @@ fn main() {
 			questing::QuestingPlugin,
 			menus::MenusPlugin,
 			inventory::InventoryPlugin,
+			loot::LootPlugin,
+			debug_draw::DebugDrawPlugin,
+			save::SavePlugin,
+			alchemy::AlchemyPlugin,
 		))
 		.add_systems(Startup, (set_window_icon, setup))
 		.add_systems(
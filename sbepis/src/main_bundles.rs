@@ -3,7 +3,7 @@ use std::f32::consts::{PI, TAU};
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
-use crate::entity::{GelViscosity, GravityOrientation, MovementInput};
+use crate::entity::{GelViscosity, GravityOrientation, MovementInput, StatusEffects};
 use crate::gravity::{GravityPoint, GravityPriority, GravityRigidbodyBundle};
 
 #[derive(Bundle)]
@@ -95,6 +95,10 @@ impl BoxBundle {
 			..self
 		}
 	}
+
+	pub fn with_collider(self, collider: Collider) -> BoxBundle {
+		BoxBundle { collider, ..self }
+	}
 }
 
 #[derive(Bundle)]
@@ -106,6 +110,7 @@ pub struct EntityBundle {
 	movement_input: MovementInput,
 	locked_axes: LockedAxes,
 	health: GelViscosity,
+	status_effects: StatusEffects,
 }
 
 impl EntityBundle {
@@ -131,6 +136,7 @@ impl EntityBundle {
 				value: 3.0,
 				max: 6.0,
 			},
+			status_effects: StatusEffects::default(),
 		}
 	}
 }
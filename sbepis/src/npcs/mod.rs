@@ -7,13 +7,22 @@ use bevy_rapier3d::geometry::Collider;
 use name_tags::*;
 
 use crate::entity::spawner::{spawn_entities, SpawnEntityInformation, SpawnedEntity, Spawner};
-use crate::entity::{Healing, RandomInput, RotateTowardMovement, SpawnHealthBar, TargetPlayer};
+use crate::entity::{
+	BeatAttacker, ChartedAttacker, DamageType, Healing, RandomInput, Resistances,
+	RotateTowardMovement, SpawnHealthBar, TargetPlayer,
+};
+use crate::fray::FrayChart;
+use crate::loot::LootDropper;
 use crate::main_bundles::EntityBundle;
+use crate::player_controller::Interactable;
 use crate::questing::{QuestGiver, SpawnQuestMarker};
 use crate::{gridbox_material, some_or_return};
 
 mod name_tags;
 
+/// Imps follow this lane of the active track's chart if it has one.
+const IMP_CHART_LANE: u8 = 0;
+
 pub struct NpcPlugin;
 impl Plugin for NpcPlugin {
 	fn build(&self, app: &mut App) {
@@ -96,6 +105,7 @@ fn spawn_consort(
 		},
 		Consort,
 		QuestGiver::default(),
+		Interactable::new("Talk to").with_priority(2),
 		SpawnQuestMarker,
 		SpawnNameTag,
 	));
@@ -107,30 +117,46 @@ fn spawn_imp(
 	mut meshes: ResMut<Assets<Mesh>>,
 	mut materials: ResMut<Assets<StandardMaterial>>,
 	asset_server: Res<AssetServer>,
+	charts: Query<&FrayChart>,
 ) {
 	let spawn_info = some_or_return!(spawn_info);
-	commands.spawn((
-		Name::new("Imp"),
-		EntityBundle::new(
-			Transform::from_translation(spawn_info.position),
-			meshes.add(
-				Capsule3d::new(0.25, 0.5)
-					.mesh()
-					.rings(1)
-					.latitudes(8)
-					.longitudes(16)
-					.uv_profile(CapsuleUvProfile::Fixed),
+	let imp = commands
+		.spawn((
+			Name::new("Imp"),
+			EntityBundle::new(
+				Transform::from_translation(spawn_info.position),
+				meshes.add(
+					Capsule3d::new(0.25, 0.5)
+						.mesh()
+						.rings(1)
+						.latitudes(8)
+						.longitudes(16)
+						.uv_profile(CapsuleUvProfile::Fixed),
+				),
+				gridbox_material("brown", &mut materials, &asset_server),
+				Collider::capsule_y(0.25, 0.25),
 			),
-			gridbox_material("brown", &mut materials, &asset_server),
-			Collider::capsule_y(0.25, 0.25),
-		),
-		SpawnHealthBar,
-		TargetPlayer,
-		RotateTowardMovement,
-		SpawnedEntity {
-			spawner: spawn_info.spawner,
-		},
-		Imp,
-		SpawnNameTag,
-	));
+			SpawnHealthBar,
+			TargetPlayer,
+			BeatAttacker::new(0.5, 1.5, 1, 2),
+			// Imp hide turns aside bullets, but it burns easily
+			Resistances::default()
+				.with(DamageType::Pierce, 0.5)
+				.with(DamageType::Fire, -0.5),
+			LootDropper(asset_server.load("loot/imp.loot.ron")),
+			RotateTowardMovement,
+			SpawnedEntity {
+				spawner: spawn_info.spawner,
+			},
+			Imp,
+			SpawnNameTag,
+		))
+		.id();
+
+	if charts.iter().any(|chart| chart.has_lane(IMP_CHART_LANE)) {
+		commands.entity(imp).insert(ChartedAttacker {
+			lane: IMP_CHART_LANE,
+			engage_range: 10.0,
+		});
+	}
 }
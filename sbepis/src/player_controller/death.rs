@@ -0,0 +1,245 @@
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::Velocity;
+use leafwing_input_manager::prelude::InputMap;
+
+use crate::camera::PlayerCameraNode;
+use crate::entity::{EntityKilled, GelViscosity, StatusEffects};
+use crate::input::input_manager;
+use crate::menus::*;
+
+use super::PlayerBody;
+
+const HEALTH_BAR_WIDTH: f32 = 300.0;
+const HEALTH_BAR_HEIGHT: f32 = 20.0;
+const HEALTH_BAR_OUTLINE: f32 = 3.0;
+
+/// Where the player appears when the game starts and after dying.
+#[derive(Resource)]
+pub struct PlayerSpawnPoint(pub Vec3);
+
+/// Which rules the game is being played by. Decides how harshly dying is punished.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameMode {
+	/// Dying only sends the player back to the spawn point
+	Casual,
+	#[default]
+	Standard,
+	Hardcore,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DeathPenalties {
+	/// Scatter everything in the inventory where the player died
+	pub drop_inventory: bool,
+	/// Abandon every quest in progress
+	pub reset_quests: bool,
+}
+
+impl GameMode {
+	/// Picks the mode from a `--casual` or `--hardcore` launch argument.
+	pub fn from_args() -> Self {
+		std::env::args()
+			.find_map(|arg| match arg.as_str() {
+				"--casual" => Some(GameMode::Casual),
+				"--hardcore" => Some(GameMode::Hardcore),
+				_ => None,
+			})
+			.unwrap_or_default()
+	}
+
+	pub fn death_penalties(self) -> DeathPenalties {
+		match self {
+			GameMode::Casual => DeathPenalties {
+				drop_inventory: false,
+				reset_quests: false,
+			},
+			GameMode::Standard => DeathPenalties {
+				drop_inventory: true,
+				reset_quests: false,
+			},
+			GameMode::Hardcore => DeathPenalties {
+				drop_inventory: true,
+				reset_quests: true,
+			},
+		}
+	}
+}
+
+/// Marks the player body while it's waiting to respawn.
+#[derive(Component)]
+pub struct Dead;
+
+/// Sent once when the player dies. Other modules hook this to apply the `GameMode`'s penalties.
+#[derive(Event)]
+pub struct PlayerDied {
+	pub body: Entity,
+	pub position: Vec3,
+}
+
+#[derive(Component)]
+pub struct DeathScreen;
+
+#[derive(Component)]
+pub struct PlayerHealthBarFill;
+
+pub fn spawn_death_screen(mut commands: Commands) {
+	commands
+		.spawn((
+			Name::new("Death Screen"),
+			NodeBundle {
+				style: Style {
+					width: Val::Percent(100.0),
+					height: Val::Percent(100.0),
+					justify_content: JustifyContent::Center,
+					align_items: AlignItems::Center,
+					..default()
+				},
+				background_color: css::DARK_RED.with_alpha(0.5).into(),
+				visibility: Visibility::Hidden,
+				..default()
+			},
+			input_manager(
+				InputMap::default().with(MenuAction::CloseMenu, KeyCode::KeyR),
+				false,
+			),
+			PlayerCameraNode,
+			Menu,
+			MenuWithInputManager,
+			MenuHidesWhenClosed,
+			DeathScreen,
+		))
+		.with_children(|parent| {
+			parent.spawn(
+				TextBundle::from_section(
+					"Your gel has run dry\nPress R to respawn",
+					TextStyle {
+						font_size: 40.0,
+						..default()
+					},
+				)
+				.with_text_justify(JustifyText::Center),
+			);
+		});
+}
+
+pub fn spawn_player_health_bar(mut commands: Commands) {
+	commands
+		.spawn((
+			Name::new("Player Health Bar"),
+			NodeBundle {
+				style: Style {
+					position_type: PositionType::Absolute,
+					left: Val::Px(10.0),
+					bottom: Val::Px(10.0),
+					width: Val::Px(HEALTH_BAR_WIDTH),
+					height: Val::Px(HEALTH_BAR_HEIGHT),
+					border: UiRect::all(Val::Px(HEALTH_BAR_OUTLINE)),
+					..default()
+				},
+				background_color: css::DARK_GRAY.with_alpha(0.5).into(),
+				border_color: css::BLACK.into(),
+				..default()
+			},
+			PlayerCameraNode,
+		))
+		.with_children(|parent| {
+			parent.spawn((
+				Name::new("Player Health Bar Fill"),
+				NodeBundle {
+					style: Style {
+						width: Val::Percent(100.0),
+						height: Val::Percent(100.0),
+						..default()
+					},
+					background_color: css::LIME.into(),
+					..default()
+				},
+				PlayerHealthBarFill,
+			));
+		});
+}
+
+pub fn update_player_health_bar(
+	player: Query<&GelViscosity, (With<PlayerBody>, Changed<GelViscosity>)>,
+	mut fills: Query<&mut Style, With<PlayerHealthBarFill>>,
+) {
+	let Ok(health) = player.get_single() else {
+		return;
+	};
+
+	let fraction = (health.value / health.max).clamp(0.0, 1.0);
+	for mut style in fills.iter_mut() {
+		style.width = Val::Percent(fraction * 100.0);
+	}
+}
+
+pub fn kill_player(
+	mut commands: Commands,
+	mut ev_killed: EventReader<EntityKilled>,
+	mut ev_died: EventWriter<PlayerDied>,
+	players: Query<&Transform, (With<PlayerBody>, Without<Dead>)>,
+	mut death_screen: Query<(Entity, &mut Visibility), With<DeathScreen>>,
+	open_menus: Query<Entity, Or<(With<MenuDespawnsWhenClosed>, With<MenuHidesWhenClosed>)>>,
+	mut menu_stack: ResMut<MenuStack>,
+) {
+	for ev in ev_killed.read() {
+		let Ok(transform) = players.get(ev.entity) else {
+			continue;
+		};
+
+		commands.entity(ev.entity).insert(Dead);
+		ev_died.send(PlayerDied {
+			body: ev.entity,
+			position: transform.translation,
+		});
+
+		// Close whatever the player had open, so respawning goes straight back to playing
+		for menu in open_menus.iter() {
+			menu_stack.remove(menu);
+		}
+
+		// Covering the player's input manager with the death screen keeps them from doing anything
+		let (death_screen, mut visibility) = death_screen
+			.get_single_mut()
+			.expect("Death screen not found");
+		*visibility = Visibility::Inherited;
+		menu_stack.push(death_screen);
+	}
+}
+
+pub fn respawn_player(
+	mut commands: Commands,
+	mut ev_deactivated: EventReader<MenuDeactivated>,
+	death_screens: Query<(), With<DeathScreen>>,
+	mut players: Query<
+		(
+			Entity,
+			&mut Transform,
+			&mut Velocity,
+			&mut GelViscosity,
+			Option<&mut StatusEffects>,
+		),
+		(With<PlayerBody>, With<Dead>),
+	>,
+	spawn_point: Res<PlayerSpawnPoint>,
+	menu_stack: Res<MenuStack>,
+) {
+	for MenuDeactivated(menu) in ev_deactivated.read() {
+		// Another menu opening over the death screen deactivates it too, without closing it
+		if !death_screens.contains(*menu) || menu_stack.contains(*menu) {
+			continue;
+		}
+
+		for (body, mut transform, mut velocity, mut health, statuses) in players.iter_mut() {
+			transform.translation = spawn_point.0;
+			*velocity = Velocity::zero();
+			health.value = health.max;
+			if let Some(mut statuses) = statuses {
+				statuses.clear();
+			}
+
+			commands.entity(body).remove::<Dead>();
+		}
+	}
+}
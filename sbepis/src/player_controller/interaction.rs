@@ -0,0 +1,211 @@
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::camera::{PlayerCamera, PlayerCameraNode};
+use crate::debug_draw::{DebugDraw, DebugShape};
+use crate::ok_or_return;
+use crate::player_controller::PlayerAction;
+
+/// How far away the player can reach things to interact with them
+const INTERACT_RANGE: f32 = 3.0;
+/// Seconds an interaction ray stays on the debug overlay
+const INTERACT_DEBUG_DURATION: f32 = 1.0;
+/// What the prompt calls the interact button
+const INTERACT_KEY_LABEL: &str = "E";
+
+/// Something the player can look at and interact with. Shows up as "[E] {action} {name}".
+#[derive(Component)]
+pub struct Interactable {
+	pub action: String,
+	/// When more than one thing is in reach, the highest priority wins, then the closest
+	pub priority: i32,
+	/// Seconds the button has to be held down, or 0 to interact straight away
+	pub hold_duration: f32,
+}
+
+impl Interactable {
+	pub fn new(action: impl Into<String>) -> Self {
+		Self {
+			action: action.into(),
+			priority: 0,
+			hold_duration: 0.0,
+		}
+	}
+
+	pub fn with_priority(self, priority: i32) -> Self {
+		Self { priority, ..self }
+	}
+
+	pub fn with_hold_duration(self, hold_duration: f32) -> Self {
+		Self {
+			hold_duration,
+			..self
+		}
+	}
+}
+
+/// What the player is looking at, worked out once a frame for everyone who wants to know.
+#[derive(Resource, Default)]
+pub struct InteractionTarget {
+	target: Option<Entity>,
+	/// How long the button has been held on the current target, if a press started on it
+	held_for: Option<f32>,
+	/// The target, on the frame the player finishes interacting with it
+	interacted: Option<Entity>,
+}
+
+#[derive(Component)]
+pub struct InteractionPrompt;
+
+pub fn update_interaction_target(
+	rapier_context: Res<RapierContext>,
+	player_camera: Query<&GlobalTransform, With<PlayerCamera>>,
+	interactables: Query<&Interactable>,
+	input: Query<&ActionState<PlayerAction>>,
+	mut interaction: ResMut<InteractionTarget>,
+	mut debug_draw: ResMut<DebugDraw>,
+	time: Res<Time>,
+) {
+	interaction.interacted = None;
+
+	// Nothing to interact with while the player's in a menu
+	let Some(input) = input.iter().find(|input| !input.disabled()) else {
+		interaction.target = None;
+		interaction.held_for = None;
+		return;
+	};
+	let player_camera = ok_or_return!(player_camera.get_single());
+
+	let start = player_camera.translation();
+	let direction: Vec3 = player_camera.forward().into();
+	let mut best: Option<(Entity, i32, f32)> = None;
+	rapier_context.intersections_with_ray(
+		start,
+		direction,
+		INTERACT_RANGE,
+		false,
+		QueryFilter::default(),
+		|entity, intersection| {
+			if let Ok(interactable) = interactables.get(entity) {
+				let distance = intersection.time_of_impact;
+				let better = match best {
+					Some((_, priority, best_distance)) => {
+						(interactable.priority, -distance) > (priority, -best_distance)
+					}
+					None => true,
+				};
+				if better {
+					best = Some((entity, interactable.priority, distance));
+				}
+			}
+			true
+		},
+	);
+	let target = best.map(|(entity, _, _)| entity);
+
+	if input.just_pressed(&PlayerAction::Interact) {
+		debug_draw.draw(
+			DebugShape::Line {
+				start,
+				end: start + direction * best.map_or(INTERACT_RANGE, |(_, _, distance)| distance),
+			},
+			if target.is_some() {
+				css::LIME
+			} else {
+				css::GRAY
+			},
+			INTERACT_DEBUG_DURATION,
+		);
+	}
+
+	// Looking away means starting the press over
+	if interaction.target != target {
+		interaction.target = target;
+		interaction.held_for = None;
+	}
+	let Some(target) = target else {
+		return;
+	};
+	let hold_duration = interactables
+		.get(target)
+		.map_or(0.0, |interactable| interactable.hold_duration);
+
+	if input.just_pressed(&PlayerAction::Interact) {
+		interaction.held_for = Some(0.0);
+	}
+	if !input.pressed(&PlayerAction::Interact) {
+		interaction.held_for = None;
+	}
+	if let Some(held_for) = interaction.held_for.as_mut() {
+		*held_for += time.delta_seconds();
+		if *held_for >= hold_duration {
+			interaction.held_for = None;
+			interaction.interacted = Some(target);
+		}
+	}
+}
+
+/// The entity the player just interacted with, if it has a `T`. Meant to be piped into an iterator system.
+pub fn interact_with<T: Component>(
+	interaction: Res<InteractionTarget>,
+	entities: Query<(), With<T>>,
+) -> Vec<Option<Entity>> {
+	interaction
+		.interacted
+		.filter(|&entity| entities.contains(entity))
+		.into_iter()
+		.map(Some)
+		.collect()
+}
+
+pub fn spawn_interaction_prompt(mut commands: Commands) {
+	commands.spawn((
+		Name::new("Interaction Prompt"),
+		TextBundle::default()
+			.with_text_justify(JustifyText::Center)
+			.with_style(Style {
+				position_type: PositionType::Absolute,
+				width: Val::Percent(100.0),
+				top: Val::Percent(60.0),
+				..default()
+			}),
+		PlayerCameraNode,
+		InteractionPrompt,
+	));
+}
+
+pub fn update_interaction_prompt(
+	interaction: Res<InteractionTarget>,
+	interactables: Query<(&Interactable, Option<&Name>)>,
+	mut prompts: Query<&mut Text, With<InteractionPrompt>>,
+) {
+	let prompt = interaction
+		.target
+		.and_then(|target| interactables.get(target).ok())
+		.map(|(interactable, name)| {
+			let name = name.map_or("", |name| name.as_str());
+			if interactable.hold_duration <= 0.0 {
+				format!("[{INTERACT_KEY_LABEL}] {} {name}", interactable.action)
+			} else {
+				let progress = interaction
+					.held_for
+					.map(|held_for| {
+						format!(" {:.0}%", held_for / interactable.hold_duration * 100.0)
+					})
+					.unwrap_or_default();
+				format!(
+					"[Hold {INTERACT_KEY_LABEL}] {} {name}{progress}",
+					interactable.action
+				)
+			}
+		})
+		.unwrap_or_default();
+
+	for mut text in prompts.iter_mut() {
+		if text.sections.first().map(|section| &section.value) != Some(&prompt) {
+			text.sections = vec![TextSection::new(prompt.clone(), TextStyle::default())];
+		}
+	}
+}
@@ -2,7 +2,9 @@ use std::f32::consts::PI;
 
 use bevy::prelude::*;
 use bevy::render::mesh::CapsuleUvProfile;
+use bevy_common_assets::ron::RonAssetPlugin;
 use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::plugin::InputManagerSystem;
 use leafwing_input_manager::prelude::*;
 
 use crate::camera::PlayerCamera;
@@ -11,19 +13,30 @@ use crate::input::*;
 use crate::inventory::Inventory;
 use crate::main_bundles::EntityBundle;
 use crate::menus::{
-	InputManagerMenuPlugin, Menu, MenuStack, MenuWithInputManager, MenuWithoutMouse,
+	show_menu, InputManagerMenuPlugin, Menu, MenuStack, MenuWithInputManager, MenuWithoutMouse,
 };
 
 use self::camera_controls::*;
-pub use self::camera_controls::{interact_with, MouseSensitivity, PlayerBody};
+pub use self::camera_controls::{MouseSensitivity, PlayerBody};
+use self::death::*;
+pub use self::death::{Dead, GameMode, PlayerDied};
+use self::interaction::*;
+pub use self::interaction::{interact_with, Interactable};
 use self::movement::*;
-use self::movement::{axes_to_ground_velocity, jump};
-use self::weapons::hammer::*;
-use self::weapons::rifle::*;
-use self::weapons::sword::*;
+use self::movement::{axes_to_ground_velocity, dodge, jump};
+use self::weapons::combo::*;
+use self::weapons::definition::*;
+use self::weapons::gun::*;
+pub use self::weapons::loadout::WeaponItem;
+use self::weapons::loadout::*;
+use self::weapons::specibus::*;
+pub use self::weapons::stats::UpgradeMaterial;
+use self::weapons::stats::*;
 use self::weapons::*;
 
 mod camera_controls;
+mod death;
+mod interaction;
 mod movement;
 mod weapons;
 
@@ -35,27 +48,71 @@ impl Plugin for PlayerControllerPlugin {
 				speed: 5.0,
 				sprint_modifier: 2.0,
 				jump_speed: 5.0,
+				encumbered: false,
 			})
-			.add_event::<EntityDamaged>()
 			.add_plugins(InputManagerMenuPlugin::<PlayerAction>::default())
-			.add_systems(Startup, setup)
+			.add_plugins(InputManagerMenuPlugin::<AbstratusAllocationAction>::default())
+			.add_plugins(RonAssetPlugin::<WeaponDefinition>::new(&["weapon.ron"]))
+			.insert_resource(PlayerSpawnPoint(Vec3::new(5.0, 10.0, 0.0)))
+			.insert_resource(GameMode::from_args())
+			.add_event::<ProjectileImpact>()
+			.add_event::<PlayerDied>()
+			.init_resource::<InteractionTarget>()
+			.add_systems(
+				Startup,
+				(
+					setup,
+					spawn_death_screen,
+					spawn_player_health_bar,
+					spawn_weapon_wheel,
+					spawn_interaction_prompt,
+					load_projectile_assets,
+				),
+			)
+			.add_systems(
+				PreUpdate,
+				update_interaction_target.after(InputManagerSystem::ManualControl),
+			)
 			.add_systems(
 				Update,
 				(
 					dual_axes_input(PlayerAction::Look).pipe(rotate_camera_and_body),
 					clamped_dual_axes_input(PlayerAction::Move).pipe(axes_to_ground_velocity),
 					jump::<PlayerBody>.run_if(button_just_pressed(PlayerAction::Jump)),
+					dodge.run_if(button_just_pressed(PlayerAction::Dodge)),
 					attack.run_if(button_just_pressed(PlayerAction::Use)),
 					switch_weapon_next.run_if(button_just_pressed(PlayerAction::NextWeapon)),
 					switch_weapon_prev.run_if(button_just_pressed(PlayerAction::PrevWeapon)),
+					spawn_weapons,
 					initialize_weapon_sets,
-					animate_hammer,
-					animate_sword,
-					animate_rifle,
-					charge_rifle,
+					animate_combos,
+					animate_guns,
+					charge_guns,
+					move_projectiles,
+					show_projectile_impacts,
 					sweep_dealers,
-					deal_all_damage,
 					update_damage_numbers,
+					show_evaded_strikes,
+					update_active_weapon_timing,
+				),
+			)
+			.add_systems(
+				Update,
+				(
+					kill_player,
+					respawn_player,
+					update_player_health_bar,
+					show_menu::<WeaponWheel>.run_if(button_just_pressed(PlayerAction::WeaponWheel)),
+					update_weapon_wheel,
+					pick_from_weapon_wheel,
+					select_quick_slots,
+					toggle_weapon_items,
+					unequip_lost_weapon_items.after(toggle_weapon_items),
+					offer_abstratus_allocation,
+					answer_abstratus_allocation,
+					upgrade_active_weapon.run_if(button_just_pressed(PlayerAction::UpgradeWeapon)),
+					update_interaction_prompt,
+					update_encumbrance,
 				),
 			);
 	}
@@ -67,6 +124,7 @@ fn setup(
 	mut materials: ResMut<Assets<StandardMaterial>>,
 	asset_server: Res<AssetServer>,
 	mut menu_stack: ResMut<MenuStack>,
+	spawn_point: Res<PlayerSpawnPoint>,
 ) {
 	let input = commands
 		.spawn((
@@ -76,10 +134,19 @@ fn setup(
 					.with(PlayerAction::Jump, KeyCode::Space)
 					.with_dual_axis(PlayerAction::Look, MouseMove::default())
 					.with(PlayerAction::Sprint, KeyCode::ShiftLeft)
+					.with(PlayerAction::Dodge, KeyCode::KeyQ)
 					.with(PlayerAction::Use, MouseButton::Left)
 					.with(PlayerAction::Interact, KeyCode::KeyE)
+					.with(PlayerAction::Throw, KeyCode::KeyG)
+					.with(PlayerAction::UseQuickItem, KeyCode::KeyR)
 					.with(PlayerAction::NextWeapon, MouseScrollDirection::UP)
 					.with(PlayerAction::PrevWeapon, MouseScrollDirection::DOWN)
+					.with(PlayerAction::WeaponWheel, KeyCode::KeyF)
+					.with(PlayerAction::QuickSlot1, KeyCode::Digit1)
+					.with(PlayerAction::QuickSlot2, KeyCode::Digit2)
+					.with(PlayerAction::QuickSlot3, KeyCode::Digit3)
+					.with(PlayerAction::QuickSlot4, KeyCode::Digit4)
+					.with(PlayerAction::UpgradeWeapon, KeyCode::KeyU)
 					.with(PlayerAction::OpenQuestScreen, KeyCode::KeyJ)
 					.with(PlayerAction::OpenInventory, KeyCode::KeyV),
 				false,
@@ -95,7 +162,7 @@ fn setup(
 		.spawn((
 			Name::new("Player Body"),
 			EntityBundle::new(
-				Transform::from_translation(Vec3::new(5.0, 10.0, 0.0)),
+				Transform::from_translation(spawn_point.0),
 				meshes.add(
 					Capsule3d::new(0.25, 1.0)
 						.mesh()
@@ -109,6 +176,19 @@ fn setup(
 			),
 			PlayerBody,
 			Inventory::default(),
+			StrifeSpecibus::new(
+				vec![
+					Abstratus::Bladekind,
+					Abstratus::Hammerkind,
+					Abstratus::Riflekind,
+				],
+				4,
+			),
+			UnspawnedWeapons(vec![
+				asset_server.load("weapons/sword.weapon.ron"),
+				asset_server.load("weapons/hammer.weapon.ron"),
+				asset_server.load("weapons/rifle.weapon.ron"),
+			]),
 		))
 		.id();
 
@@ -129,38 +209,6 @@ fn setup(
 		.set_parent(body)
 		.id();
 
-	let (hammer_pivot, _hammer_head) = spawn_hammer(
-		&mut commands,
-		&asset_server,
-		&mut materials,
-		&mut meshes,
-		body,
-	);
-
-	let (sword_pivot, _sword_blade) = spawn_sword(
-		&mut commands,
-		&asset_server,
-		&mut materials,
-		&mut meshes,
-		body,
-	);
-
-	let (rifle_pivot, _rifle_barrel) = spawn_rifle(
-		&mut commands,
-		&asset_server,
-		&mut materials,
-		&mut meshes,
-		body,
-	);
-
-	commands.entity(body).insert((
-		WeaponSet {
-			weapons: vec![sword_pivot, hammer_pivot, rifle_pivot],
-			active_weapon: 0,
-		},
-		UninitializedWeaponSet,
-	));
-
 	commands.spawn((
 		Name::new("Damage Numbers"),
 		TextBundle::from_section("Damage", TextStyle::default()).with_style(Style {
@@ -172,13 +220,6 @@ fn setup(
 		DamageNumbers,
 		TargetCamera(camera),
 	));
-
-	commands.spawn((
-		Name::new("Debug Collider Visualizer"),
-		DebugColliderVisualizer,
-		SpatialBundle::default(),
-		CollisionGroups::new(Group::NONE, Group::NONE),
-	));
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Hash, Reflect, Debug)]
@@ -187,10 +228,19 @@ pub enum PlayerAction {
 	Jump,
 	Look,
 	Sprint,
+	Dodge,
 	Use,
 	Interact,
+	Throw,
+	UseQuickItem,
 	NextWeapon,
 	PrevWeapon,
+	WeaponWheel,
+	QuickSlot1,
+	QuickSlot2,
+	QuickSlot3,
+	QuickSlot4,
+	UpgradeWeapon,
 	OpenQuestScreen,
 	OpenInventory,
 }
@@ -201,10 +251,19 @@ impl Actionlike for PlayerAction {
 			PlayerAction::Jump => InputControlKind::Button,
 			PlayerAction::Look => InputControlKind::DualAxis,
 			PlayerAction::Sprint => InputControlKind::Button,
+			PlayerAction::Dodge => InputControlKind::Button,
 			PlayerAction::Use => InputControlKind::Button,
 			PlayerAction::Interact => InputControlKind::Button,
+			PlayerAction::Throw => InputControlKind::Button,
+			PlayerAction::UseQuickItem => InputControlKind::Button,
 			PlayerAction::NextWeapon => InputControlKind::Button,
 			PlayerAction::PrevWeapon => InputControlKind::Button,
+			PlayerAction::WeaponWheel => InputControlKind::Button,
+			PlayerAction::QuickSlot1 => InputControlKind::Button,
+			PlayerAction::QuickSlot2 => InputControlKind::Button,
+			PlayerAction::QuickSlot3 => InputControlKind::Button,
+			PlayerAction::QuickSlot4 => InputControlKind::Button,
+			PlayerAction::UpgradeWeapon => InputControlKind::Button,
 			PlayerAction::OpenQuestScreen => InputControlKind::Button,
 			PlayerAction::OpenInventory => InputControlKind::Button,
 		}
@@ -2,15 +2,25 @@ use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
 
-use crate::entity::MovementInput;
+use crate::entity::{Evading, Evasion, MovementInput};
+use crate::inventory::Inventory;
+use crate::ok_or_return;
 
 use super::{PlayerAction, PlayerBody};
 
+/// How many beats a dodge keeps the player from getting hit
+const DODGE_WINDOW: f32 = 0.5;
+const DODGE_COOLDOWN: f32 = 1.0;
+/// How much of their jump the player keeps while their sylladex is weighing them down
+const ENCUMBERED_JUMP_MODIFIER: f32 = 0.6;
+
 #[derive(Resource)]
 pub struct PlayerSpeed {
 	pub speed: f32,
 	pub sprint_modifier: f32,
 	pub jump_speed: f32,
+	/// Carrying too much turns off sprinting and shortens jumps
+	pub encumbered: bool,
 }
 
 pub fn axes_to_ground_velocity(
@@ -24,7 +34,7 @@ pub fn axes_to_ground_velocity(
 	axes_input.y *= -1.;
 	let velocity = axes_input
 		* speed.speed
-		* if key_input.pressed(&PlayerAction::Sprint) {
+		* if key_input.pressed(&PlayerAction::Sprint) && !speed.encumbered {
 			speed.sprint_modifier
 		} else {
 			1.0
@@ -37,5 +47,33 @@ pub fn jump<Marker: Component>(
 	speed: Res<PlayerSpeed>,
 ) {
 	let (mut velocity, transform) = player_body.single_mut();
-	velocity.linvel += transform.up() * speed.jump_speed;
+	let jump_speed = if speed.encumbered {
+		speed.jump_speed * ENCUMBERED_JUMP_MODIFIER
+	} else {
+		speed.jump_speed
+	};
+	velocity.linvel += transform.up() * jump_speed;
+}
+
+pub fn update_encumbrance(
+	inventories: Query<Ref<Inventory>, With<PlayerBody>>,
+	mut speed: ResMut<PlayerSpeed>,
+) {
+	let inventory = ok_or_return!(inventories.get_single());
+	if inventory.is_changed() && speed.encumbered != inventory.is_encumbered() {
+		speed.encumbered = inventory.is_encumbered();
+	}
+}
+
+pub fn dodge(
+	mut commands: Commands,
+	player_body: Query<Entity, (With<PlayerBody>, Without<Evading>)>,
+) {
+	for player_body in player_body.iter() {
+		commands.entity(player_body).insert(Evading::new(
+			Evasion::Dodge,
+			DODGE_WINDOW,
+			DODGE_COOLDOWN,
+		));
+	}
 }
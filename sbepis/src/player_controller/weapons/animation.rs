@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+use interpolation::{Ease, EaseFunction};
+use serde::Deserialize;
+
+use crate::util::MapRange;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum KeyframeEase {
+	#[default]
+	Linear,
+	QuadraticIn,
+	QuadraticOut,
+	QuadraticInOut,
+	CubicIn,
+	CubicOut,
+	CubicInOut,
+	QuarticIn,
+	QuarticOut,
+	QuarticInOut,
+	ExponentialIn,
+	ExponentialOut,
+	ExponentialInOut,
+}
+
+impl KeyframeEase {
+	fn ease(self, t: f32) -> f32 {
+		let function = match self {
+			KeyframeEase::Linear => return t,
+			KeyframeEase::QuadraticIn => EaseFunction::QuadraticIn,
+			KeyframeEase::QuadraticOut => EaseFunction::QuadraticOut,
+			KeyframeEase::QuadraticInOut => EaseFunction::QuadraticInOut,
+			KeyframeEase::CubicIn => EaseFunction::CubicIn,
+			KeyframeEase::CubicOut => EaseFunction::CubicOut,
+			KeyframeEase::CubicInOut => EaseFunction::CubicInOut,
+			KeyframeEase::QuarticIn => EaseFunction::QuarticIn,
+			KeyframeEase::QuarticOut => EaseFunction::QuarticOut,
+			KeyframeEase::QuarticInOut => EaseFunction::QuarticInOut,
+			KeyframeEase::ExponentialIn => EaseFunction::ExponentialIn,
+			KeyframeEase::ExponentialOut => EaseFunction::ExponentialOut,
+			KeyframeEase::ExponentialInOut => EaseFunction::ExponentialInOut,
+		};
+		t.calc(function)
+	}
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WeaponPose {
+	pub translation: Vec3,
+	/// Euler angles in degrees, applied yaw first
+	pub rotation: Vec3,
+}
+
+impl WeaponPose {
+	pub fn transform(&self) -> Transform {
+		Transform::from_translation(self.translation).with_rotation(Quat::from_euler(
+			EulerRot::YXZ,
+			self.rotation.y.to_radians(),
+			self.rotation.x.to_radians(),
+			self.rotation.z.to_radians(),
+		))
+	}
+}
+
+/// A pose of the weapon pivot, relative to where it rests, at some point in an animation.
+#[derive(Clone, Debug, Deserialize)]
+pub struct WeaponKeyframe {
+	/// Beats since the animation started
+	pub beat: f32,
+	#[serde(default)]
+	pub pose: WeaponPose,
+	/// How to ease into this keyframe from the previous one
+	#[serde(default)]
+	pub ease: KeyframeEase,
+}
+
+/// Where the pivot sits when it isn't being animated.
+#[derive(Component)]
+pub struct WeaponPivot {
+	pub rest: Transform,
+}
+
+/// How many beats the keyframes take to play out.
+pub fn animation_length(keyframes: &[WeaponKeyframe]) -> f32 {
+	keyframes.last().map_or(0.0, |keyframe| keyframe.beat)
+}
+
+/// Samples the keyframes, which are sorted by beat, at a point in the animation.
+pub fn pose_at(keyframes: &[WeaponKeyframe], beat: f32) -> Transform {
+	match keyframes
+		.windows(2)
+		.find(|keyframes| beat < keyframes[1].beat)
+	{
+		Some([from, to]) if beat > from.beat => {
+			// Lerping the angles rather than slerping lets keyframes spin the whole way around
+			let t = to.ease.ease(beat.map_to_01(from.beat..to.beat));
+			WeaponPose {
+				translation: from.pose.translation.lerp(to.pose.translation, t),
+				rotation: from.pose.rotation.lerp(to.pose.rotation, t),
+			}
+			.transform()
+		}
+		Some(keyframes) => keyframes[0].pose.transform(),
+		None => keyframes
+			.last()
+			.map_or(Transform::IDENTITY, |keyframe| keyframe.pose.transform()),
+	}
+}
+
+/// Moves a pivot to a pose relative to its rest.
+pub fn apply_pose(transform: &mut Transform, pivot: &WeaponPivot, pose: Transform) {
+	*transform = pivot.rest.mul_transform(pose);
+}
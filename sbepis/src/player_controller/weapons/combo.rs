@@ -0,0 +1,302 @@
+use bevy::ecs::entity::EntityHashSet;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::entity::{DamageType, EntityDamaged, StatusEffect};
+use crate::fray::FrayMusic;
+use crate::ok_or_continue;
+use crate::util::{sample_curve, MapRange};
+
+use super::animation::{animation_length, apply_pose, pose_at, WeaponKeyframe, WeaponPivot};
+use super::definition::WeaponSound;
+use super::stats::WeaponStats;
+use super::{DamageSweep, EndDamageSweep, InAnimation, SweepPivot};
+
+/// How far from the nearest beat a click can be and still count as on beat, in beats
+const ON_BEAT_WINDOW: f32 = 0.15;
+
+/// What was going on when the player clicked, used to pick which attack comes next.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AttackContext {
+	pub on_beat: bool,
+	pub sprinting: bool,
+}
+
+impl AttackContext {
+	pub fn new(fray: &FrayMusic, sprinting: bool) -> Self {
+		let beat_progress = fray.beat_progress();
+		Self {
+			on_beat: beat_progress.min(1.0 - beat_progress) <= ON_BEAT_WINDOW,
+			sprinting,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum ComboCondition {
+	Any,
+	OnBeat,
+	OffBeat,
+	Sprinting,
+}
+
+impl ComboCondition {
+	pub fn matches(self, context: AttackContext) -> bool {
+		match self {
+			ComboCondition::Any => true,
+			ComboCondition::OnBeat => context.on_beat,
+			ComboCondition::OffBeat => !context.on_beat,
+			ComboCondition::Sprinting => context.sprinting,
+		}
+	}
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ComboAttack {
+	pub name: String,
+	pub condition: ComboCondition,
+	/// Sorted by beat. The attack ends on the last one.
+	pub keyframes: Vec<WeaponKeyframe>,
+	pub sweep: SweepPivot,
+	/// Beats into the attack the damage sweep starts
+	pub sweep_start: f32,
+	/// Beats into the attack the damage sweep ends
+	pub sweep_end: f32,
+	pub damage: f32,
+	#[serde(default)]
+	pub damage_type: DamageType,
+	/// Applied to everything the attack hits
+	#[serde(default)]
+	pub effects: Vec<StatusEffect>,
+	/// Damage multiplier over the course of the sweep, as (progress, multiplier) points
+	pub damage_curve: Vec<(f32, f32)>,
+	/// Beats into the attack before a buffered click can cut it short
+	pub cancel_from: f32,
+	/// Indices of the attacks that can follow this one, in order of priority
+	pub follow_ups: Vec<usize>,
+	#[serde(default)]
+	pub swing_sound: Option<WeaponSound>,
+	#[serde(default)]
+	pub impact_sound: Option<WeaponSound>,
+}
+
+impl ComboAttack {
+	pub fn length(&self) -> f32 {
+		animation_length(&self.keyframes)
+	}
+
+	fn sweep_progress(&self, beat: f32) -> f32 {
+		if self.sweep_end <= self.sweep_start {
+			return 1.0;
+		}
+		beat.map_to_01(self.sweep_start..self.sweep_end)
+			.clamp(0.0, 1.0)
+	}
+
+	fn damage_multiplier(&self, progress: f32) -> f32 {
+		sample_curve(&self.damage_curve, progress).unwrap_or(1.0)
+	}
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ComboString {
+	pub attacks: Vec<ComboAttack>,
+	/// Indices of the attacks that can start a string, in order of priority
+	pub openers: Vec<usize>,
+	/// How many beats after an attack finishes a click still follows up on it. `None` never breaks the string.
+	pub link_window: Option<f32>,
+}
+
+/// Marks a weapon pivot as animated by a `MeleeCombo`.
+#[derive(Component)]
+pub struct ComboPivot;
+
+/// A click waiting for the current attack to reach its cancel window.
+#[derive(Component)]
+pub struct BufferedAttack(pub AttackContext);
+
+#[derive(Component)]
+pub struct MeleeCombo {
+	pub name: String,
+	pub string: ComboString,
+	pub pivot: Entity,
+	pub allies: EntityHashSet,
+	current_attack: Option<CurrentAttack>,
+	/// The last attack to finish, and the beat it finished on
+	last_attack: Option<(usize, f64)>,
+}
+
+struct CurrentAttack {
+	attack: usize,
+	fray_modifier: f32,
+	damaged: EntityHashSet,
+}
+
+impl MeleeCombo {
+	pub fn new(
+		name: impl Into<String>,
+		string: ComboString,
+		pivot: Entity,
+		allies: EntityHashSet,
+	) -> Self {
+		Self {
+			name: name.into(),
+			string,
+			pivot,
+			allies,
+			current_attack: None,
+			last_attack: None,
+		}
+	}
+
+	/// Which attack a click with this context would start right now.
+	pub fn next_attack(&self, context: AttackContext, beat: f64) -> Option<usize> {
+		let previous_attack = match (&self.current_attack, self.last_attack) {
+			(Some(current_attack), _) => Some(current_attack.attack),
+			(None, Some((attack, finished_beat)))
+				if self
+					.string
+					.link_window
+					.is_none_or(|link_window| beat - finished_beat <= link_window as f64) =>
+			{
+				Some(attack)
+			}
+			_ => None,
+		};
+
+		let candidates = match previous_attack.map(|attack| &self.string.attacks[attack].follow_ups)
+		{
+			Some(follow_ups) if !follow_ups.is_empty() => follow_ups,
+			_ => &self.string.openers,
+		};
+		candidates
+			.iter()
+			.copied()
+			.find(|attack| self.string.attacks[*attack].condition.matches(context))
+	}
+}
+
+pub fn animate_combos(
+	mut commands: Commands,
+	mut combos: Query<(
+		Entity,
+		&mut MeleeCombo,
+		&GlobalTransform,
+		Option<&DamageSweep>,
+	)>,
+	mut pivots: Query<
+		(
+			&mut Transform,
+			&WeaponPivot,
+			&WeaponStats,
+			Option<&mut InAnimation>,
+			Option<&BufferedAttack>,
+		),
+		With<ComboPivot>,
+	>,
+	time: Res<Time>,
+	fray: Query<&FrayMusic>,
+	mut ev_hit: EventWriter<EntityDamaged>,
+	asset_server: Res<AssetServer>,
+) {
+	let fray = fray.get_single().expect("Could not find fray");
+	for (head_entity, mut combo, head_transform, dealer) in combos.iter_mut() {
+		let pivot_entity = combo.pivot;
+		let (mut transform, weapon_pivot, stats, animation, buffered) =
+			ok_or_continue!(pivots.get_mut(pivot_entity));
+
+		let prev_time = animation.as_ref().map_or(0.0, |animation| {
+			fray.time_to_bpm_beat(animation.time) as f32
+		});
+
+		if let Some(BufferedAttack(context)) = buffered {
+			let cancellable = combo.current_attack.as_ref().is_none_or(|current_attack| {
+				prev_time >= combo.string.attacks[current_attack.attack].cancel_from
+			});
+			if cancellable {
+				commands.entity(pivot_entity).remove::<BufferedAttack>();
+
+				if let Some(next_attack) = combo.next_attack(*context, fray.beat()) {
+					let attack = &combo.string.attacks[next_attack];
+					if let Some(swing_sound) = &attack.swing_sound {
+						swing_sound.play(&mut commands, &asset_server, &attack.name);
+					}
+
+					commands
+						.entity(head_entity)
+						.remove::<(DamageSweep, EndDamageSweep)>();
+					commands
+						.entity(pivot_entity)
+						.insert((InAnimation::default(), stats.scale_sweep(&attack.sweep)));
+					combo.current_attack = Some(CurrentAttack {
+						attack: next_attack,
+						fray_modifier: fray.modify_fray_damage(1.0),
+						damaged: EntityHashSet::default(),
+					});
+					continue;
+				}
+			}
+		}
+
+		let Some(mut animation) = animation else {
+			continue;
+		};
+		let MeleeCombo {
+			string,
+			allies,
+			current_attack,
+			last_attack,
+			..
+		} = &mut *combo;
+		let Some(current) = current_attack.as_mut() else {
+			continue;
+		};
+		let attack = &string.attacks[current.attack];
+
+		animation.time += time.delta().mul_f32(stats.speed);
+		let curr_time = fray.time_to_bpm_beat(animation.time) as f32;
+
+		if (prev_time..curr_time).contains(&attack.sweep_start) {
+			commands.entity(head_entity).insert(DamageSweep::new(
+				*head_transform,
+				pivot_entity,
+				allies.clone(),
+			));
+		}
+		if (prev_time..curr_time).contains(&attack.sweep_end) {
+			commands.entity(head_entity).insert(EndDamageSweep);
+
+			if let Some(impact_sound) = &attack.impact_sound {
+				impact_sound.play(&mut commands, &asset_server, &attack.name);
+			}
+		}
+
+		if let Some(dealer) = dealer.filter(|_| curr_time >= attack.sweep_start) {
+			let multiplier = attack.damage_multiplier(attack.sweep_progress(curr_time));
+			for (entity, direction) in dealer.hit_entities.iter() {
+				if current.damaged.insert(*entity) {
+					ev_hit.send(EntityDamaged {
+						victim: *entity,
+						damage: attack.damage * stats.damage * multiplier * current.fray_modifier,
+						fray_modifier: current.fray_modifier,
+						damage_type: attack.damage_type,
+						effects: attack.effects.clone(),
+						knockback: *direction,
+					});
+				}
+			}
+		}
+
+		apply_pose(
+			&mut transform,
+			weapon_pivot,
+			pose_at(&attack.keyframes, curr_time),
+		);
+
+		if curr_time >= attack.length() {
+			commands.entity(pivot_entity).remove::<InAnimation>();
+			*last_attack = Some((current.attack, fray.beat()));
+			*current_attack = None;
+		}
+	}
+}
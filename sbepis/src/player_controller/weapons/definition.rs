@@ -0,0 +1,242 @@
+use bevy::asset::LoadState;
+use bevy::ecs::entity::EntityHashSet;
+use bevy::gltf::GltfAssetLabel;
+use bevy::prelude::*;
+use bevy::render::mesh::CapsuleUvProfile;
+use serde::Deserialize;
+
+use crate::gridbox_material;
+use crate::save::SaveData;
+
+use super::animation::{WeaponPivot, WeaponPose};
+use super::combo::{ComboPivot, ComboString, MeleeCombo};
+use super::gun::{Gun, GunPivot, GunShot};
+use super::specibus::Abstratus;
+use super::stats::{WeaponStats, WeaponUpgrade};
+use super::{UninitializedWeaponSet, WeaponSet};
+
+/// Everything needed to spawn and use a weapon, loaded from a `.weapon.ron` file.
+#[derive(Asset, Deserialize, TypePath)]
+pub struct WeaponDefinition {
+	pub name: String,
+	pub kind: Abstratus,
+	pub model: WeaponModel,
+	/// Where the pivot rests relative to the body. Animations are relative to this.
+	#[serde(default)]
+	pub pivot: WeaponPose,
+	/// Where the model sits relative to the pivot
+	#[serde(default)]
+	pub model_offset: WeaponPose,
+	pub behaviour: WeaponBehaviour,
+	/// Each level the weapon can be upgraded to, in order
+	#[serde(default)]
+	pub upgrades: Vec<WeaponUpgrade>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum WeaponModel {
+	/// A gridbox-textured capsule lying along the model's Y axis
+	Capsule {
+		radius: f32,
+		length: f32,
+		material: String,
+	},
+	/// The first scene of a GLTF file
+	Scene(String),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum WeaponBehaviour {
+	Melee(ComboString),
+	Gun(GunShot),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct WeaponSound {
+	pub path: String,
+	#[serde(default = "WeaponSound::default_speed")]
+	pub speed: f32,
+}
+
+impl WeaponSound {
+	fn default_speed() -> f32 {
+		1.0
+	}
+
+	pub fn play(&self, commands: &mut Commands, asset_server: &AssetServer, name: &str) {
+		commands.spawn((
+			Name::new(format!("{name} SFX")),
+			AudioBundle {
+				source: asset_server.load(self.path.clone()),
+				settings: PlaybackSettings::DESPAWN.with_speed(self.speed),
+			},
+		));
+	}
+}
+
+/// Sits on a weapon's pivot.
+#[derive(Component)]
+pub struct Weapon {
+	pub name: String,
+	/// The inventory item this weapon was equipped from. Starting weapons don't have one.
+	pub item: Option<Entity>,
+}
+
+/// Weapons that get spawned onto this body and put in its `WeaponSet` once they've all loaded.
+#[derive(Component)]
+pub struct UnspawnedWeapons(pub Vec<Handle<WeaponDefinition>>);
+
+pub fn spawn_weapon(
+	commands: &mut Commands,
+	asset_server: &AssetServer,
+	materials: &mut Assets<StandardMaterial>,
+	meshes: &mut Assets<Mesh>,
+	definition: &WeaponDefinition,
+	body: Entity,
+	item: Option<Entity>,
+	level: u32,
+) -> Entity {
+	let rest = definition.pivot.transform();
+	let pivot = commands
+		.spawn((
+			Name::new(format!("{} Pivot", definition.name)),
+			SpatialBundle::from_transform(rest),
+			WeaponPivot { rest },
+			Weapon {
+				name: definition.name.clone(),
+				item,
+			},
+			WeaponStats::new(definition.upgrades.clone(), level),
+		))
+		.set_parent(body)
+		.id();
+
+	let transform = definition.model_offset.transform();
+	let model = match &definition.model {
+		WeaponModel::Capsule {
+			radius,
+			length,
+			material,
+		} => commands
+			.spawn(PbrBundle {
+				transform,
+				mesh: meshes.add(
+					Capsule3d::new(*radius, *length)
+						.mesh()
+						.rings(1)
+						.latitudes(8)
+						.longitudes(16)
+						.uv_profile(CapsuleUvProfile::Fixed),
+				),
+				material: gridbox_material(material, materials, asset_server),
+				..default()
+			})
+			.id(),
+		WeaponModel::Scene(path) => commands
+			.spawn(SceneBundle {
+				scene: asset_server.load(GltfAssetLabel::Scene(0).from_asset(path.clone())),
+				transform,
+				..default()
+			})
+			.id(),
+	};
+	commands
+		.entity(model)
+		.insert(Name::new(format!("{} Model", definition.name)))
+		.set_parent(pivot);
+
+	let allies = EntityHashSet::from_iter(vec![body]);
+	match &definition.behaviour {
+		WeaponBehaviour::Melee(string) => {
+			commands.entity(pivot).insert(ComboPivot);
+			commands.entity(model).insert(MeleeCombo::new(
+				definition.name.clone(),
+				string.clone(),
+				pivot,
+				allies,
+			));
+		}
+		WeaponBehaviour::Gun(shot) => {
+			commands.entity(pivot).insert(GunPivot);
+			commands.entity(model).insert(Gun::new(
+				definition.name.clone(),
+				shot.clone(),
+				pivot,
+				allies,
+			));
+		}
+	}
+
+	pivot
+}
+
+pub fn spawn_weapons(
+	mut commands: Commands,
+	bodies: Query<(Entity, &UnspawnedWeapons)>,
+	definitions: Res<Assets<WeaponDefinition>>,
+	asset_server: Res<AssetServer>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+	mut meshes: ResMut<Assets<Mesh>>,
+	save_data: Res<SaveData>,
+) {
+	let failed = |handle: &Handle<WeaponDefinition>| {
+		matches!(
+			asset_server.get_load_state(handle),
+			Some(LoadState::Failed(_))
+		)
+	};
+
+	for (body, unspawned_weapons) in bodies.iter() {
+		// Wait until every definition has either loaded or failed
+		if unspawned_weapons
+			.0
+			.iter()
+			.any(|handle| definitions.get(handle).is_none() && !failed(handle))
+		{
+			continue;
+		}
+
+		// A broken definition shouldn't cost the player the rest of their weapons
+		let mut loaded = Vec::new();
+		for handle in unspawned_weapons.0.iter() {
+			match definitions.get(handle) {
+				Some(definition) => loaded.push(definition),
+				None => warn!(
+					"Couldn't load weapon {}",
+					handle
+						.path()
+						.map_or("???".to_owned(), |path| path.to_string())
+				),
+			}
+		}
+
+		commands.entity(body).remove::<UnspawnedWeapons>();
+		if loaded.is_empty() {
+			continue;
+		}
+
+		let weapons = loaded
+			.into_iter()
+			.map(|definition| {
+				spawn_weapon(
+					&mut commands,
+					&asset_server,
+					&mut materials,
+					&mut meshes,
+					definition,
+					body,
+					None,
+					save_data.weapon_level(&definition.name),
+				)
+			})
+			.collect();
+
+		commands.entity(body).insert((
+			WeaponSet {
+				weapons,
+				active_weapon: 0,
+			},
+			UninitializedWeaponSet,
+		));
+	}
+}
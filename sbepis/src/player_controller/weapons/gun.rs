@@ -0,0 +1,487 @@
+use bevy::color::palettes::css;
+use bevy::ecs::entity::EntityHashSet;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier3d::math::Real;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::camera::PlayerCamera;
+use crate::debug_draw::{DebugDraw, DebugShape};
+use crate::entity::{DamageType, EntityDamaged, GelViscosity, StatusEffect};
+use crate::fray::FrayMusic;
+use crate::gravity::GravityRigidbodyBundle;
+use crate::util::{sample_curve, DespawnTimer};
+use crate::{gridbox_material, ok_or_continue};
+
+use super::animation::{animation_length, apply_pose, pose_at, WeaponKeyframe, WeaponPivot};
+use super::definition::WeaponSound;
+use super::stats::WeaponStats;
+use super::InAnimation;
+
+const IMPACT_RADIUS: f32 = 0.1;
+const IMPACT_LIFETIME: f32 = 0.2;
+/// How far a hitscan shot that hit nothing is drawn on the debug overlay
+const HITSCAN_DEBUG_LENGTH: f32 = 100.0;
+/// Seconds raycasts stay on the debug overlay
+const RAYCAST_DEBUG_DURATION: f32 = 1.0;
+
+/// Fires at the crosshair when clicked, charging up on the beat between shots.
+#[derive(Clone, Debug, Deserialize)]
+pub struct GunShot {
+	pub damage: f32,
+	#[serde(default)]
+	pub damage_type: DamageType,
+	/// Applied to everything a shot hits
+	#[serde(default)]
+	pub effects: Vec<StatusEffect>,
+	pub ammo: GunAmmo,
+	/// How many times per beat the weapon gains charge
+	pub charge_rate: u32,
+	pub max_charge: u32,
+	pub full_charge_multiplier: f32,
+	/// Plays after each shot. The weapon can't fire again until it's done.
+	pub recoil: Vec<WeaponKeyframe>,
+	#[serde(default)]
+	pub shot_sound: Option<WeaponSound>,
+	#[serde(default)]
+	pub charge_sound: Option<WeaponSound>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum GunAmmo {
+	/// Instantly hits whatever's under the crosshair
+	Hitscan,
+	/// Fires a physical projectile that falls under gravity
+	Projectile(ProjectileStats),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProjectileStats {
+	pub speed: f32,
+	pub radius: f32,
+	pub material: String,
+	/// Seconds before the projectile despawns if it hasn't hit anything solid
+	pub lifetime: f32,
+	/// Damage multiplier by distance travelled, as (distance, multiplier) points
+	#[serde(default)]
+	pub drop_off: Vec<(f32, f32)>,
+	/// How many damageable entities the projectile can pass through before it stops
+	#[serde(default)]
+	pub penetration: u32,
+	#[serde(default)]
+	pub impact_sound: Option<WeaponSound>,
+}
+
+/// Marks a weapon pivot as animated by a `Gun`.
+#[derive(Component)]
+pub struct GunPivot;
+
+#[derive(Component)]
+pub struct Gun {
+	pub name: String,
+	pub shot: GunShot,
+	pub pivot: Entity,
+	pub allies: EntityHashSet,
+	pub charge: u32,
+	pub last_beat: u32,
+}
+
+impl Gun {
+	pub fn new(
+		name: impl Into<String>,
+		shot: GunShot,
+		pivot: Entity,
+		allies: EntityHashSet,
+	) -> Self {
+		Self {
+			name: name.into(),
+			shot,
+			pivot,
+			allies,
+			charge: 0,
+			last_beat: 0,
+		}
+	}
+
+	fn update_last_beat(&mut self, fray: &FrayMusic) {
+		self.last_beat = fray.subbeats(self.shot.charge_rate);
+	}
+
+	fn get_beat(&self, fray: &FrayMusic) -> u32 {
+		fray.subbeats(self.shot.charge_rate)
+	}
+}
+
+/// Meshes and materials shared by every projectile and impact, so firing doesn't keep making new ones.
+#[derive(Resource)]
+pub struct ProjectileAssets {
+	impact_mesh: Handle<Mesh>,
+	impact_hit_material: Handle<StandardMaterial>,
+	impact_miss_material: Handle<StandardMaterial>,
+	/// Projectiles look different per gun, so these are filled in the first time each is fired
+	projectile_meshes: HashMap<u32, Handle<Mesh>>,
+	projectile_materials: HashMap<String, Handle<StandardMaterial>>,
+}
+
+impl ProjectileAssets {
+	fn projectile(
+		&mut self,
+		stats: &ProjectileStats,
+		meshes: &mut Assets<Mesh>,
+		materials: &mut Assets<StandardMaterial>,
+		asset_server: &AssetServer,
+	) -> (Handle<Mesh>, Handle<StandardMaterial>) {
+		let mesh = self
+			.projectile_meshes
+			.entry(stats.radius.to_bits())
+			.or_insert_with(|| meshes.add(Sphere::new(stats.radius)))
+			.clone();
+		let material = self
+			.projectile_materials
+			.entry(stats.material.clone())
+			.or_insert_with(|| gridbox_material(&stats.material, materials, asset_server))
+			.clone();
+		(mesh, material)
+	}
+}
+
+pub fn load_projectile_assets(
+	mut commands: Commands,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+	let mut impact_material = |color: Srgba| {
+		materials.add(StandardMaterial {
+			base_color: color.into(),
+			unlit: true,
+			..default()
+		})
+	};
+	commands.insert_resource(ProjectileAssets {
+		impact_mesh: meshes.add(Sphere::new(IMPACT_RADIUS)),
+		impact_hit_material: impact_material(css::RED),
+		impact_miss_material: impact_material(css::GRAY),
+		projectile_meshes: HashMap::default(),
+		projectile_materials: HashMap::default(),
+	});
+}
+
+#[derive(Component)]
+pub struct Projectile {
+	pub damage: f32,
+	pub fray_modifier: f32,
+	pub damage_type: DamageType,
+	pub effects: Vec<StatusEffect>,
+	pub drop_off: Vec<(f32, f32)>,
+	pub penetration: u32,
+	pub allies: EntityHashSet,
+	pub impact_sound: Option<WeaponSound>,
+	hit_entities: EntityHashSet,
+	last_position: Vec3,
+	distance: f32,
+}
+
+#[derive(Event)]
+pub struct ProjectileImpact {
+	pub victim: Entity,
+	pub position: Vec3,
+}
+
+fn spawn_projectile(
+	commands: &mut Commands,
+	asset_server: &AssetServer,
+	materials: &mut Assets<StandardMaterial>,
+	meshes: &mut Assets<Mesh>,
+	projectile_assets: &mut ProjectileAssets,
+	stats: &ProjectileStats,
+	position: Vec3,
+	direction: Vec3,
+	damage: f32,
+	fray_modifier: f32,
+	damage_type: DamageType,
+	effects: Vec<StatusEffect>,
+	allies: EntityHashSet,
+) {
+	let (mesh, material) = projectile_assets.projectile(stats, meshes, materials, asset_server);
+	commands.spawn((
+		Name::new("Projectile"),
+		PbrBundle {
+			transform: Transform::from_translation(position),
+			mesh,
+			material,
+			..default()
+		},
+		GravityRigidbodyBundle {
+			velocity: Velocity::linear(direction * stats.speed),
+			..default()
+		},
+		Collider::ball(stats.radius),
+		Sensor,
+		DespawnTimer::new(stats.lifetime),
+		Projectile {
+			damage,
+			fray_modifier,
+			damage_type,
+			effects,
+			drop_off: stats.drop_off.clone(),
+			penetration: stats.penetration,
+			allies,
+			impact_sound: stats.impact_sound.clone(),
+			hit_entities: EntityHashSet::default(),
+			last_position: position,
+			distance: 0.0,
+		},
+	));
+}
+
+pub fn animate_guns(
+	mut commands: Commands,
+	mut guns: Query<(&mut Gun, &GlobalTransform)>,
+	mut pivots: Query<
+		(
+			Entity,
+			&mut Transform,
+			&WeaponPivot,
+			&WeaponStats,
+			&mut InAnimation,
+		),
+		With<GunPivot>,
+	>,
+	time: Res<Time>,
+	fray: Query<&FrayMusic>,
+	mut ev_hit: EventWriter<EntityDamaged>,
+	asset_server: Res<AssetServer>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut projectile_assets: ResMut<ProjectileAssets>,
+	rapier_context: Res<RapierContext>,
+	player_camera: Query<&GlobalTransform, With<PlayerCamera>>,
+	mut debug_draw: ResMut<DebugDraw>,
+) {
+	let fray = fray.get_single().expect("Could not find fray");
+	for (mut gun, barrel_transform) in guns.iter_mut() {
+		let (pivot_entity, mut transform, weapon_pivot, stats, mut animation) =
+			ok_or_continue!(pivots.get_mut(gun.pivot));
+
+		let prev_time = fray.time_to_bpm_beat(animation.time) as f32;
+		animation.time += time.delta().mul_f32(stats.speed);
+		let curr_time = fray.time_to_bpm_beat(animation.time) as f32;
+
+		let reload_time = animation_length(&gun.shot.recoil);
+
+		if (prev_time..curr_time).contains(&0.0) {
+			if let Some(shot_sound) = &gun.shot.shot_sound {
+				shot_sound.play(&mut commands, &asset_server, &format!("{} Shot", gun.name));
+			}
+
+			let charge_multiplier = if gun.charge >= gun.shot.max_charge + stats.bonus_charge {
+				gun.shot.full_charge_multiplier
+			} else {
+				1.0
+			};
+			gun.charge = 0;
+			let damage =
+				fray.modify_fray_damage(gun.shot.damage) * stats.damage * charge_multiplier;
+			let fray_modifier = fray.modify_fray_damage(1.0);
+
+			let player_camera = player_camera.get_single().expect("Player camera not found");
+			match &gun.shot.ammo {
+				GunAmmo::Hitscan => {
+					let start = player_camera.translation();
+					let direction: Vec3 = player_camera.forward().into();
+					let hit = rapier_context.cast_ray(
+						start,
+						direction,
+						Real::MAX,
+						false,
+						QueryFilter::new().predicate(&|entity| !gun.allies.contains(&entity)),
+					);
+					debug_draw.draw(
+						DebugShape::Line {
+							start,
+							end: start
+								+ direction
+									* hit.map_or(HITSCAN_DEBUG_LENGTH, |(_, distance)| distance),
+						},
+						css::RED,
+						RAYCAST_DEBUG_DURATION,
+					);
+					if let Some((hit_entity, _distance)) = hit {
+						ev_hit.send(EntityDamaged {
+							victim: hit_entity,
+							damage,
+							fray_modifier,
+							damage_type: gun.shot.damage_type,
+							effects: gun.shot.effects.clone(),
+							knockback: direction,
+						});
+					}
+				}
+				GunAmmo::Projectile(stats) => spawn_projectile(
+					&mut commands,
+					&asset_server,
+					&mut materials,
+					&mut meshes,
+					&mut projectile_assets,
+					stats,
+					barrel_transform.translation(),
+					player_camera.forward().into(),
+					damage,
+					fray_modifier,
+					gun.shot.damage_type,
+					gun.shot.effects.clone(),
+					gun.allies.clone(),
+				),
+			}
+		}
+		if curr_time >= reload_time {
+			commands.entity(pivot_entity).remove::<InAnimation>();
+			gun.update_last_beat(fray);
+		}
+
+		apply_pose(
+			&mut transform,
+			weapon_pivot,
+			pose_at(&gun.shot.recoil, curr_time),
+		);
+	}
+}
+
+pub fn charge_guns(
+	mut commands: Commands,
+	mut guns: Query<&mut Gun>,
+	pivots: Query<&WeaponStats, (With<GunPivot>, Without<InAnimation>)>,
+	fray: Query<&FrayMusic>,
+	asset_server: Res<AssetServer>,
+) {
+	let fray = fray.get_single().expect("Could not find fray");
+	for mut gun in guns.iter_mut() {
+		let stats = ok_or_continue!(pivots.get(gun.pivot));
+
+		let beat = gun.get_beat(fray);
+		if gun.charge < gun.shot.max_charge + stats.bonus_charge && gun.last_beat != beat {
+			gun.charge += 1;
+
+			if let Some(charge_sound) = &gun.shot.charge_sound {
+				charge_sound.play(
+					&mut commands,
+					&asset_server,
+					&format!("{} Charge", gun.name),
+				);
+			}
+		}
+		gun.update_last_beat(fray);
+	}
+}
+
+/// Checks what each projectile passed through since last frame, in order, until something stops it.
+pub fn move_projectiles(
+	mut commands: Commands,
+	mut projectiles: Query<(Entity, &mut Projectile, &Transform)>,
+	damageable: Query<(), With<GelViscosity>>,
+	rapier_context: Res<RapierContext>,
+	mut ev_hit: EventWriter<EntityDamaged>,
+	mut ev_impact: EventWriter<ProjectileImpact>,
+	asset_server: Res<AssetServer>,
+	mut debug_draw: ResMut<DebugDraw>,
+) {
+	for (projectile_entity, mut projectile, transform) in projectiles.iter_mut() {
+		let start = projectile.last_position;
+		let delta = transform.translation - start;
+		let length = delta.length();
+		if length <= 0.0 {
+			continue;
+		}
+		let direction = delta / length;
+		debug_draw.draw(
+			DebugShape::Line {
+				start,
+				end: transform.translation,
+			},
+			css::ORANGE_RED,
+			RAYCAST_DEBUG_DURATION,
+		);
+
+		let mut hits = Vec::new();
+		rapier_context.intersections_with_ray(
+			start,
+			direction,
+			length,
+			true,
+			QueryFilter::new()
+				.exclude_sensors()
+				.predicate(&|entity| !projectile.allies.contains(&entity)),
+			|entity, intersection| {
+				hits.push((entity, intersection.time_of_impact));
+				true
+			},
+		);
+		hits.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+		for (hit_entity, time_of_impact) in hits {
+			if !projectile.hit_entities.insert(hit_entity) {
+				continue;
+			}
+
+			ev_impact.send(ProjectileImpact {
+				victim: hit_entity,
+				position: start + direction * time_of_impact,
+			});
+			if let Some(impact_sound) = &projectile.impact_sound {
+				impact_sound.play(&mut commands, &asset_server, "Projectile Impact");
+			}
+
+			let damageable = damageable.contains(hit_entity);
+			if damageable {
+				let drop_off =
+					sample_curve(&projectile.drop_off, projectile.distance + time_of_impact)
+						.unwrap_or(1.0);
+				ev_hit.send(EntityDamaged {
+					victim: hit_entity,
+					damage: projectile.damage * drop_off,
+					fray_modifier: projectile.fray_modifier,
+					damage_type: projectile.damage_type,
+					effects: projectile.effects.clone(),
+					knockback: direction,
+				});
+			}
+
+			// Anything without health is solid enough to stop a projectile outright
+			if !damageable || projectile.penetration == 0 {
+				commands.entity(projectile_entity).despawn_recursive();
+				break;
+			}
+			projectile.penetration -= 1;
+		}
+
+		projectile.distance += length;
+		projectile.last_position = transform.translation;
+	}
+}
+
+pub fn show_projectile_impacts(
+	mut commands: Commands,
+	mut ev_impact: EventReader<ProjectileImpact>,
+	damageable: Query<(), With<GelViscosity>>,
+	projectile_assets: Res<ProjectileAssets>,
+) {
+	for impact in ev_impact.read() {
+		let material = if damageable.contains(impact.victim) {
+			projectile_assets.impact_hit_material.clone()
+		} else {
+			projectile_assets.impact_miss_material.clone()
+		};
+
+		commands.spawn((
+			Name::new("Projectile Impact"),
+			PbrBundle {
+				transform: Transform::from_translation(impact.position),
+				mesh: projectile_assets.impact_mesh.clone(),
+				material,
+				..default()
+			},
+			DespawnTimer::new(IMPACT_LIFETIME),
+		));
+	}
+}
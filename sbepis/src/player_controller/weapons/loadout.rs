@@ -0,0 +1,254 @@
+use std::f32::consts::TAU;
+
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::camera::PlayerCameraNode;
+use crate::input::input_manager;
+use crate::inventory::{Inventory, ItemUsed};
+use crate::menus::*;
+use crate::player_controller::{PlayerAction, PlayerBody};
+use crate::save::SaveData;
+use crate::{ok_or_continue, ok_or_return, some_or_continue};
+
+use super::definition::{spawn_weapon, Weapon, WeaponDefinition};
+use super::specibus::StrifeSpecibus;
+use super::{hide_weapon, select_weapon, WeaponSet};
+
+const WHEEL_RADIUS: f32 = 150.0;
+const WHEEL_SLOT_WIDTH: f32 = 140.0;
+const WHEEL_SLOT_HEIGHT: f32 = 40.0;
+
+/// The actions that pull out each weapon in the `WeaponSet`, in order.
+pub const QUICK_SLOTS: [PlayerAction; 4] = [
+	PlayerAction::QuickSlot1,
+	PlayerAction::QuickSlot2,
+	PlayerAction::QuickSlot3,
+	PlayerAction::QuickSlot4,
+];
+
+/// An inventory item that can be equipped into the player's `WeaponSet` from the inventory screen.
+#[derive(Component)]
+pub struct WeaponItem(pub Handle<WeaponDefinition>);
+
+#[derive(Component)]
+pub struct WeaponWheel;
+
+#[derive(Component)]
+pub struct WeaponWheelSlot(pub usize);
+
+pub fn spawn_weapon_wheel(mut commands: Commands) {
+	commands.spawn((
+		Name::new("Weapon Wheel"),
+		NodeBundle {
+			style: Style {
+				width: Val::Percent(100.0),
+				height: Val::Percent(100.0),
+				..default()
+			},
+			background_color: css::BLACK.with_alpha(0.25).into(),
+			visibility: Visibility::Hidden,
+			..default()
+		},
+		input_manager(
+			InputMap::default().with(MenuAction::CloseMenu, KeyCode::KeyF),
+			false,
+		),
+		PlayerCameraNode,
+		Menu,
+		MenuWithMouse,
+		MenuWithInputManager,
+		MenuHidesWhenClosed,
+		WeaponWheel,
+	));
+}
+
+pub fn update_weapon_wheel(
+	mut commands: Commands,
+	weapon_sets: Query<&WeaponSet, (With<PlayerBody>, Changed<WeaponSet>)>,
+	weapons: Query<&Weapon>,
+	wheel: Query<Entity, With<WeaponWheel>>,
+) {
+	let weapon_set = ok_or_return!(weapon_sets.get_single());
+	let wheel = wheel.get_single().expect("Weapon wheel not found");
+
+	commands.entity(wheel).despawn_descendants();
+	for (index, weapon) in weapon_set.weapons.iter().enumerate() {
+		let name = weapons
+			.get(*weapon)
+			.map_or("???", |weapon| weapon.name.as_str());
+		let angle = index as f32 / weapon_set.weapons.len() as f32 * TAU;
+		let offset = Vec2::new(angle.sin(), -angle.cos()) * WHEEL_RADIUS;
+		let color = if index == weapon_set.active_weapon {
+			css::GOLDENROD
+		} else {
+			css::DARK_GRAY
+		};
+
+		commands
+			.spawn((
+				ButtonBundle {
+					style: Style {
+						position_type: PositionType::Absolute,
+						left: Val::Percent(50.0),
+						top: Val::Percent(50.0),
+						width: Val::Px(WHEEL_SLOT_WIDTH),
+						height: Val::Px(WHEEL_SLOT_HEIGHT),
+						margin: UiRect {
+							left: Val::Px(offset.x - WHEEL_SLOT_WIDTH * 0.5),
+							top: Val::Px(offset.y - WHEEL_SLOT_HEIGHT * 0.5),
+							..default()
+						},
+						justify_content: JustifyContent::Center,
+						align_items: AlignItems::Center,
+						..default()
+					},
+					background_color: color.with_alpha(0.8).into(),
+					..default()
+				},
+				WeaponWheelSlot(index),
+			))
+			.with_children(|parent| {
+				parent.spawn(TextBundle::from_section(
+					format!("{}: {name}", index + 1),
+					TextStyle::default(),
+				));
+			})
+			.set_parent(wheel);
+	}
+}
+
+pub fn pick_from_weapon_wheel(
+	mut commands: Commands,
+	slots: Query<(&WeaponWheelSlot, &Interaction), Changed<Interaction>>,
+	mut weapon_sets: Query<&mut WeaponSet, With<PlayerBody>>,
+	wheel: Query<Entity, With<WeaponWheel>>,
+	mut menu_stack: ResMut<MenuStack>,
+) {
+	for (WeaponWheelSlot(index), interaction) in slots.iter() {
+		if *interaction != Interaction::Pressed {
+			continue;
+		}
+
+		for mut weapon_set in weapon_sets.iter_mut() {
+			select_weapon(&mut commands, &mut weapon_set, *index);
+		}
+		for wheel in wheel.iter() {
+			menu_stack.remove(wheel);
+		}
+	}
+}
+
+pub fn select_quick_slots(
+	mut commands: Commands,
+	input: Query<&ActionState<PlayerAction>>,
+	mut weapon_sets: Query<&mut WeaponSet, With<PlayerBody>>,
+) {
+	let Some(input) = input.iter().find(|input| !input.disabled()) else {
+		return;
+	};
+	let Some(slot) = QUICK_SLOTS
+		.iter()
+		.position(|action| input.just_pressed(action))
+	else {
+		return;
+	};
+
+	for mut weapon_set in weapon_sets.iter_mut() {
+		select_weapon(&mut commands, &mut weapon_set, slot);
+	}
+}
+
+/// Equips weapon items used from the inventory screen, or unequips them if they already are.
+/// Only weapons whose kind is allocated to the player's `StrifeSpecibus` can be equipped.
+pub fn toggle_weapon_items(
+	mut commands: Commands,
+	mut ev_used: EventReader<ItemUsed>,
+	weapon_items: Query<&WeaponItem>,
+	weapons: Query<&Weapon>,
+	mut bodies: Query<(Entity, &mut WeaponSet, &StrifeSpecibus), With<PlayerBody>>,
+	definitions: Res<Assets<WeaponDefinition>>,
+	asset_server: Res<AssetServer>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+	mut meshes: ResMut<Assets<Mesh>>,
+	save_data: Res<SaveData>,
+) {
+	for ItemUsed(item) in ev_used.read() {
+		let weapon_item = ok_or_continue!(weapon_items.get(*item));
+		let (body, mut weapon_set, specibus) = ok_or_continue!(bodies.get_single_mut());
+
+		let equipped = weapon_set.weapons.iter().position(|weapon| {
+			weapons
+				.get(*weapon)
+				.is_ok_and(|weapon| weapon.item == Some(*item))
+		});
+		match equipped {
+			Some(index) => unequip_weapon(&mut commands, &mut weapon_set, index),
+			None => {
+				let definition = some_or_continue!(definitions.get(&weapon_item.0));
+				if !specibus.can_wield(definition.kind) {
+					continue;
+				}
+				let pivot = spawn_weapon(
+					&mut commands,
+					&asset_server,
+					&mut materials,
+					&mut meshes,
+					definition,
+					body,
+					Some(*item),
+					save_data.weapon_level(&definition.name),
+				);
+				hide_weapon(&mut commands, pivot);
+				weapon_set.weapons.push(pivot);
+			}
+		}
+	}
+}
+
+/// Unequips weapons whose items aren't in the inventory anymore, however they left it.
+pub fn unequip_lost_weapon_items(
+	mut commands: Commands,
+	weapons: Query<&Weapon>,
+	mut bodies: Query<(&mut WeaponSet, &Inventory), (With<PlayerBody>, Changed<Inventory>)>,
+) {
+	for (mut weapon_set, inventory) in bodies.iter_mut() {
+		let lost: Vec<usize> = weapon_set
+			.weapons
+			.iter()
+			.enumerate()
+			.filter(|(_, weapon)| {
+				weapons.get(**weapon).is_ok_and(|weapon| {
+					weapon
+						.item
+						.is_some_and(|item| !inventory.items().any(|held| held == item))
+				})
+			})
+			.map(|(index, _)| index)
+			.collect();
+
+		// Backwards, so removing one doesn't shift the rest
+		for index in lost.into_iter().rev() {
+			unequip_weapon(&mut commands, &mut weapon_set, index);
+		}
+	}
+}
+
+fn unequip_weapon(commands: &mut Commands, weapon_set: &mut WeaponSet, index: usize) {
+	// There always has to be something in hand
+	if weapon_set.weapons.len() <= 1 {
+		return;
+	}
+
+	if index == weapon_set.active_weapon {
+		let other_weapon = if index == 0 { 1 } else { index - 1 };
+		select_weapon(commands, weapon_set, other_weapon);
+	}
+
+	let pivot = weapon_set.weapons.remove(index);
+	commands.entity(pivot).despawn_recursive();
+	if index < weapon_set.active_weapon {
+		weapon_set.active_weapon -= 1;
+	}
+}
@@ -1,32 +1,39 @@
 use std::time::Duration;
 
 use bevy::color::palettes::css;
-use bevy::ecs::entity::EntityHashSet;
+use bevy::ecs::entity::{EntityHashMap, EntityHashSet};
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::Deserialize;
 
-use crate::entity::{EntityKilled, GelViscosity};
+use crate::debug_draw::{DebugDraw, DebugShape};
+use crate::entity::{EntityDamaged, Evading, Evasion, GelViscosity, StatusEffects, StrikeEvaded};
+use crate::fray::{ActiveWeaponTiming, FrayMusic};
+use crate::player_controller::{PlayerAction, PlayerBody};
 use crate::util::QuaternionEx;
 
-pub mod hammer;
-pub mod rifle;
-pub mod sword;
+use self::combo::{AttackContext, BufferedAttack, ComboPivot, MeleeCombo};
+use self::gun::Gun;
+use self::stats::WeaponStats;
+
+pub mod animation;
+pub mod combo;
+pub mod definition;
+pub mod gun;
+pub mod loadout;
+pub mod specibus;
+pub mod stats;
 
 #[derive(Component, Default)]
 pub struct InAnimation {
 	pub time: Duration,
 }
 
-#[derive(Event)]
-pub struct EntityDamaged {
-	pub victim: Entity,
-	pub damage: f32,
-	pub fray_modifier: f32,
-}
-
 #[derive(Component)]
 pub struct DamageNumbers;
 
+/// The weapons a body can switch between, by pivot.
 #[derive(Component)]
 pub struct WeaponSet {
 	pub weapons: Vec<Entity>,
@@ -41,7 +48,8 @@ pub struct ActiveWeapon;
 
 #[derive(Component)]
 pub struct DamageSweep {
-	pub hit_entities: EntityHashSet,
+	/// Everything the sweep has hit, and which way it was moving when it hit them
+	pub hit_entities: EntityHashMap<Vec3>,
 	pub last_transform: GlobalTransform,
 	pub pivot: Entity,
 	pub allies: EntityHashSet,
@@ -50,7 +58,7 @@ pub struct DamageSweep {
 #[derive(Component)]
 pub struct EndDamageSweep;
 
-#[derive(Component)]
+#[derive(Component, Clone, Debug, Deserialize)]
 pub struct SweepPivot {
 	pub sweeper_length: f32,
 	pub sweep_depth: f32,
@@ -60,7 +68,7 @@ pub struct SweepPivot {
 impl DamageSweep {
 	pub fn new(transform: GlobalTransform, pivot: Entity, allies: EntityHashSet) -> Self {
 		Self {
-			hit_entities: EntityHashSet::default(),
+			hit_entities: EntityHashMap::default(),
 			last_transform: transform,
 			pivot,
 			allies,
@@ -68,15 +76,49 @@ impl DamageSweep {
 	}
 }
 
-#[derive(Component)]
-pub struct DebugColliderVisualizer;
+/// How many beats before a strike a swing has to start to parry it
+const PARRY_WINDOW: f32 = 0.25;
+const PARRY_COOLDOWN: f32 = 0.5;
+/// Seconds each slice of a sweep stays on the debug overlay, so whole swings can be seen
+const SWEEP_DEBUG_DURATION: f32 = 1.0;
 
 pub fn attack(
 	mut commands: Commands,
-	weapons: Query<Entity, (Without<InAnimation>, With<ActiveWeapon>)>,
+	weapons: Query<(Entity, Has<InAnimation>, Has<ComboPivot>), With<ActiveWeapon>>,
+	player_bodies: Query<Entity, (With<PlayerBody>, Without<Evading>)>,
+	player_statuses: Query<&StatusEffects, With<PlayerBody>>,
+	fray: Query<&FrayMusic>,
+	input: Query<&ActionState<PlayerAction>>,
 ) {
-	for weapon in weapons.iter() {
-		commands.entity(weapon).insert(InAnimation::default());
+	if player_statuses.iter().any(StatusEffects::is_stunned) {
+		return;
+	}
+
+	let fray = fray.get_single().expect("Could not find fray");
+	let sprinting = input
+		.iter()
+		.find(|input| !input.disabled())
+		.is_some_and(|input| input.pressed(&PlayerAction::Sprint));
+
+	for (weapon, in_animation, combo) in weapons.iter() {
+		if combo {
+			// Combos decide for themselves when the click gets used
+			commands
+				.entity(weapon)
+				.insert(BufferedAttack(AttackContext::new(fray, sprinting)));
+		} else if in_animation {
+			continue;
+		} else {
+			commands.entity(weapon).insert(InAnimation::default());
+		}
+
+		for player_body in player_bodies.iter() {
+			commands.entity(player_body).insert(Evading::new(
+				Evasion::Parry,
+				PARRY_WINDOW,
+				PARRY_COOLDOWN,
+			));
+		}
 	}
 }
 
@@ -90,9 +132,8 @@ pub fn sweep_dealers(
 	)>,
 	pivots: Query<(&SweepPivot, &GlobalTransform), Without<DamageSweep>>,
 	rapier_context: Res<RapierContext>,
-	debug_collider_visualizers: Query<Entity, With<DebugColliderVisualizer>>,
+	mut debug_draw: ResMut<DebugDraw>,
 ) {
-	let debug_collider_visualizer = debug_collider_visualizers.single();
 	for (dealer_entity, mut dealer, end, transform) in dealers.iter_mut() {
 		let (pivot, pivot_transform) = pivots.get(dealer.pivot).expect("Sweep pivot not found");
 
@@ -101,6 +142,7 @@ pub fn sweep_dealers(
 			.transform_point(pivot.sweeper_length * 0.5 * Vec3::Z);
 		let end_tip = transform.transform_point(pivot.sweeper_length * 0.5 * Vec3::NEG_Z);
 		let delta = end_tip - start_tip;
+		let direction = delta.normalize_or_zero();
 		let position = (end_tip + start_tip) * 0.5;
 
 		let pivot_position = pivot_transform.translation();
@@ -119,15 +161,20 @@ pub fn sweep_dealers(
 			QueryFilter::new(),
 			|hit_entity| {
 				if !dealer.allies.contains(&hit_entity) {
-					dealer.hit_entities.insert(hit_entity);
+					dealer.hit_entities.entry(hit_entity).or_insert(direction);
 				}
 				true
 			},
 		);
-		commands
-			.entity(debug_collider_visualizer)
-			.insert(collider)
-			.insert(Transform::from_translation(position).with_rotation(rotation));
+		debug_draw.draw(
+			DebugShape::Cuboid(Transform {
+				translation: position,
+				rotation,
+				scale: Vec3::new(pivot.sweep_depth, pivot.sweep_height, delta.length()),
+			}),
+			css::ORANGE,
+			SWEEP_DEBUG_DURATION,
+		);
 
 		dealer.last_transform = *transform;
 
@@ -140,27 +187,6 @@ pub fn sweep_dealers(
 	}
 }
 
-pub fn deal_all_damage(
-	mut ev_hit: EventReader<EntityDamaged>,
-	mut ev_kill: EventWriter<EntityKilled>,
-	mut healths: Query<&mut GelViscosity>,
-) {
-	for event in ev_hit.read() {
-		let Ok(mut health) = healths.get_mut(event.victim) else {
-			continue;
-		};
-		let damage = event.damage;
-
-		if damage > 0.0 && health.value <= 0.0 {
-			ev_kill.send(EntityKilled {
-				entity: event.victim,
-			});
-		}
-
-		health.value -= damage;
-	}
-}
-
 pub fn update_damage_numbers(
 	mut ev_hit: EventReader<EntityDamaged>,
 	mut damage_numbers: Query<&mut Text, With<DamageNumbers>>,
@@ -189,6 +215,32 @@ pub fn update_damage_numbers(
 	}
 }
 
+pub fn show_evaded_strikes(
+	mut ev_evaded: EventReader<StrikeEvaded>,
+	mut damage_numbers: Query<&mut Text, With<DamageNumbers>>,
+	attackers: Query<&Name>,
+) {
+	for event in ev_evaded.read() {
+		let Ok(attacker_name) = attackers.get(event.attacker) else {
+			continue;
+		};
+
+		let verb = match event.evasion {
+			Evasion::Dodge => "Dodged",
+			Evasion::Parry => "Parried",
+		};
+		for mut damage_numbers in damage_numbers.iter_mut() {
+			damage_numbers.sections.push(TextSection::new(
+				format!("\n{verb} {attacker_name}"),
+				TextStyle {
+					color: css::AQUA.into(),
+					..default()
+				},
+			));
+		}
+	}
+}
+
 pub fn initialize_weapon_sets(
 	mut commands: Commands,
 	weapon_sets: Query<(Entity, &WeaponSet), With<UninitializedWeaponSet>>,
@@ -207,25 +259,73 @@ pub fn initialize_weapon_sets(
 
 pub fn switch_weapon_next(mut commands: Commands, mut weapon_sets: Query<&mut WeaponSet>) {
 	for mut weapon_set in weapon_sets.iter_mut() {
-		let old_weapon = weapon_set.weapons[weapon_set.active_weapon];
-		hide_weapon(&mut commands, old_weapon);
-		weapon_set.active_weapon = (weapon_set.active_weapon + 1) % weapon_set.weapons.len();
-		let new_weapon = weapon_set.weapons[weapon_set.active_weapon];
-		show_weapon(&mut commands, new_weapon);
+		let next_weapon = (weapon_set.active_weapon + 1) % weapon_set.weapons.len();
+		select_weapon(&mut commands, &mut weapon_set, next_weapon);
 	}
 }
 
 pub fn switch_weapon_prev(mut commands: Commands, mut weapon_sets: Query<&mut WeaponSet>) {
 	for mut weapon_set in weapon_sets.iter_mut() {
-		let old_weapon = weapon_set.weapons[weapon_set.active_weapon];
-		hide_weapon(&mut commands, old_weapon);
-		weapon_set.active_weapon =
+		let prev_weapon =
 			(weapon_set.active_weapon + weapon_set.weapons.len() - 1) % weapon_set.weapons.len();
-		let new_weapon = weapon_set.weapons[weapon_set.active_weapon];
-		show_weapon(&mut commands, new_weapon);
+		select_weapon(&mut commands, &mut weapon_set, prev_weapon);
 	}
 }
 
+/// Puts away the weapon in hand and pulls out the one at `index`, if there is one.
+pub fn select_weapon(commands: &mut Commands, weapon_set: &mut WeaponSet, index: usize) {
+	if index >= weapon_set.weapons.len() || index == weapon_set.active_weapon {
+		return;
+	}
+
+	hide_weapon(commands, weapon_set.weapons[weapon_set.active_weapon]);
+	weapon_set.active_weapon = index;
+	show_weapon(commands, weapon_set.weapons[index]);
+}
+
+pub fn update_active_weapon_timing(
+	mut timing: ResMut<ActiveWeaponTiming>,
+	active_weapons: Query<(), With<ActiveWeapon>>,
+	combos: Query<&MeleeCombo>,
+	guns: Query<&Gun>,
+	stats: Query<&WeaponStats>,
+	fray: Query<&FrayMusic>,
+) {
+	let fray = fray.get_single().expect("Could not find fray");
+	let (name, subdivisions, impact_offset) = if let Some(combo) = combos
+		.iter()
+		.find(|combo| active_weapons.get(combo.pivot).is_ok())
+	{
+		match combo.next_attack(AttackContext::new(fray, false), fray.beat()) {
+			Some(next_attack) => {
+				let attack = &combo.string.attacks[next_attack];
+				let speed = stats.get(combo.pivot).map_or(1.0, |stats| stats.speed);
+				(
+					format!("{}: {}", combo.name, attack.name),
+					1,
+					attack.sweep_end / speed,
+				)
+			}
+			None => (combo.name.clone(), 1, 0.0),
+		}
+	} else if let Some(gun) = guns
+		.iter()
+		.find(|gun| active_weapons.get(gun.pivot).is_ok())
+	{
+		(gun.name.clone(), gun.shot.charge_rate, 0.0)
+	} else {
+		// Nothing in hand, so there's no timing to show
+		timing.set_if_neq(ActiveWeaponTiming::default());
+		return;
+	};
+
+	timing.set_if_neq(ActiveWeaponTiming {
+		name,
+		subdivisions,
+		impact_offset,
+	});
+}
+
 fn hide_weapon(commands: &mut Commands, weapon: Entity) {
 	commands
 		.entity(weapon)
@@ -0,0 +1,253 @@
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::Deserialize;
+
+use crate::camera::PlayerCameraNode;
+use crate::input::input_manager;
+use crate::inventory::ItemPickedUp;
+use crate::menus::*;
+use crate::player_controller::PlayerBody;
+use crate::{ok_or_continue, some_or_continue};
+
+use super::definition::WeaponDefinition;
+use super::loadout::WeaponItem;
+
+/// The kind of weapon something is. Only weapons of a kind allocated to the wielder's `StrifeSpecibus` can be equipped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum Abstratus {
+	Hammerkind,
+	Bladekind,
+	Riflekind,
+	Forkkind,
+}
+
+impl Abstratus {
+	pub fn name(self) -> &'static str {
+		match self {
+			Abstratus::Hammerkind => "hammerkind",
+			Abstratus::Bladekind => "bladekind",
+			Abstratus::Riflekind => "riflekind",
+			Abstratus::Forkkind => "forkkind",
+		}
+	}
+}
+
+/// The kinds of weapon a player can wield. Allocating an abstratus is permanent.
+#[derive(Component)]
+pub struct StrifeSpecibus {
+	pub abstrati: Vec<Abstratus>,
+	/// How many abstrati can be allocated in total
+	pub slots: usize,
+	/// Kinds the player has already been asked about, so declining one sticks
+	offered: Vec<Abstratus>,
+}
+
+impl StrifeSpecibus {
+	pub fn new(abstrati: Vec<Abstratus>, slots: usize) -> Self {
+		Self {
+			abstrati,
+			slots,
+			offered: Vec::new(),
+		}
+	}
+
+	pub fn can_wield(&self, kind: Abstratus) -> bool {
+		self.abstrati.contains(&kind)
+	}
+
+	fn should_offer(&self, kind: Abstratus) -> bool {
+		!self.can_wield(kind) && !self.offered.contains(&kind) && self.abstrati.len() < self.slots
+	}
+}
+
+#[derive(Component)]
+pub struct AbstratusAllocation {
+	pub abstratus: Abstratus,
+}
+
+#[derive(Component)]
+pub struct AbstratusAllocationButton {
+	pub allocation: Entity,
+	pub allocate: bool,
+}
+
+/// Asks the player whether to allocate a weapon's kind the first time they captchalogue a weapon they can't wield.
+pub fn offer_abstratus_allocation(
+	mut commands: Commands,
+	mut ev_picked_up: EventReader<ItemPickedUp>,
+	weapon_items: Query<&WeaponItem>,
+	mut specibi: Query<&mut StrifeSpecibus, With<PlayerBody>>,
+	definitions: Res<Assets<WeaponDefinition>>,
+	mut menu_stack: ResMut<MenuStack>,
+) {
+	for ItemPickedUp(item) in ev_picked_up.read() {
+		let weapon_item = ok_or_continue!(weapon_items.get(*item));
+		let definition = some_or_continue!(definitions.get(&weapon_item.0));
+		let mut specibus = ok_or_continue!(specibi.get_single_mut());
+		if !specibus.should_offer(definition.kind) {
+			continue;
+		}
+
+		specibus.offered.push(definition.kind);
+		let allocation = spawn_allocation_prompt(&mut commands, definition, &specibus);
+		menu_stack.push(allocation);
+	}
+}
+
+fn spawn_allocation_prompt(
+	commands: &mut Commands,
+	definition: &WeaponDefinition,
+	specibus: &StrifeSpecibus,
+) -> Entity {
+	let kind = definition.kind;
+	commands
+		.spawn((
+			Name::new(format!("Allocate {}", kind.name())),
+			NodeBundle {
+				style: Style {
+					margin: UiRect::all(Val::Auto),
+					width: Val::Percent(100.0),
+					max_width: Val::Px(600.0),
+					padding: UiRect::all(Val::Px(10.0)),
+					flex_direction: FlexDirection::Column,
+					..default()
+				},
+				background_color: css::GRAY.into(),
+				..default()
+			},
+			PlayerCameraNode,
+			input_manager(
+				InputMap::default()
+					.with(AbstratusAllocationAction::Allocate, KeyCode::KeyY)
+					.with(AbstratusAllocationAction::Decline, KeyCode::KeyN),
+				false,
+			),
+			Menu,
+			MenuWithMouse,
+			MenuWithInputManager,
+			MenuDespawnsWhenClosed,
+			AbstratusAllocation { abstratus: kind },
+		))
+		.with_children(|parent| {
+			let allocation = parent.parent_entity();
+
+			parent.spawn(TextBundle {
+				text: Text::from_section(
+					format!(
+						"Allocate {} to your strife specibus?\n\nYou can't wield the {} without it, and this can't be undone. {} of {} abstrati allocated.",
+						kind.name(),
+						definition.name,
+						specibus.abstrati.len(),
+						specibus.slots,
+					),
+					TextStyle {
+						font_size: 20.0,
+						color: Color::WHITE,
+						..default()
+					},
+				),
+				style: Style {
+					margin: UiRect::bottom(Val::Px(10.0)),
+					..default()
+				},
+				..default()
+			});
+			parent
+				.spawn(NodeBundle {
+					style: Style {
+						flex_direction: FlexDirection::Row,
+						column_gap: Val::Px(10.0),
+						..default()
+					},
+					..default()
+				})
+				.with_children(|parent| {
+					for (allocate, label) in [(true, "Allocate [Y]"), (false, "Decline [N]")] {
+						parent
+							.spawn((
+								ButtonBundle {
+									style: Style {
+										padding: UiRect::all(Val::Px(10.0)),
+										flex_grow: 1.0,
+										..default()
+									},
+									background_color: css::DARK_GRAY.into(),
+									..default()
+								},
+								AbstratusAllocationButton {
+									allocation,
+									allocate,
+								},
+							))
+							.with_children(|parent| {
+								parent.spawn(TextBundle::from_section(
+									label,
+									TextStyle {
+										font_size: 20.0,
+										color: Color::WHITE,
+										..default()
+									},
+								));
+							});
+					}
+				});
+		})
+		.id()
+}
+
+pub fn answer_abstratus_allocation(
+	allocations: Query<(
+		Entity,
+		&AbstratusAllocation,
+		&ActionState<AbstratusAllocationAction>,
+	)>,
+	buttons: Query<(&AbstratusAllocationButton, &Interaction), Changed<Interaction>>,
+	mut specibi: Query<&mut StrifeSpecibus, With<PlayerBody>>,
+	mut menu_stack: ResMut<MenuStack>,
+) {
+	let inputs = allocations
+		.iter()
+		.filter(|(_, _, input)| !input.disabled())
+		.filter_map(|(allocation, _, input)| {
+			if input.just_pressed(&AbstratusAllocationAction::Allocate) {
+				Some((allocation, true))
+			} else if input.just_pressed(&AbstratusAllocationAction::Decline) {
+				Some((allocation, false))
+			} else {
+				None
+			}
+		});
+	let clicks = buttons
+		.iter()
+		.filter(|(_, &interaction)| interaction == Interaction::Pressed)
+		.map(|(button, _)| (button.allocation, button.allocate));
+	let answers: Vec<(Entity, bool)> = inputs.chain(clicks).collect();
+
+	for (allocation, allocate) in answers {
+		let (_, AbstratusAllocation { abstratus }, _) =
+			ok_or_continue!(allocations.get(allocation));
+		if allocate {
+			for mut specibus in specibi.iter_mut() {
+				if !specibus.can_wield(*abstratus) && specibus.abstrati.len() < specibus.slots {
+					specibus.abstrati.push(*abstratus);
+				}
+			}
+		}
+		menu_stack.remove(allocation);
+	}
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Reflect, Debug)]
+pub enum AbstratusAllocationAction {
+	Allocate,
+	Decline,
+}
+impl Actionlike for AbstratusAllocationAction {
+	fn input_control_kind(&self) -> InputControlKind {
+		match self {
+			AbstratusAllocationAction::Allocate => InputControlKind::Button,
+			AbstratusAllocationAction::Decline => InputControlKind::Button,
+		}
+	}
+}
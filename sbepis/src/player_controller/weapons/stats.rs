@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::inventory::Inventory;
+use crate::player_controller::PlayerBody;
+use crate::save::SaveData;
+use crate::{ok_or_return, some_or_return};
+
+use super::definition::Weapon;
+use super::{ActiveWeapon, SweepPivot};
+
+/// An item that gets used up upgrading weapons.
+#[derive(Component)]
+pub struct UpgradeMaterial;
+
+/// One level of a weapon's progression, loaded as part of its `.weapon.ron`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct WeaponUpgrade {
+	/// How many `UpgradeMaterial`s it takes to reach this level
+	pub materials: u32,
+	pub modifiers: Vec<WeaponModifier>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum WeaponModifier {
+	/// Multiplies the damage of every hit
+	Damage(f32),
+	/// Multiplies how fast attacks and recoil play out
+	Speed(f32),
+	/// Multiplies the depth and height of melee sweeps
+	SweepSize(f32),
+	/// Lets guns hold more charge
+	MaxCharge(u32),
+}
+
+/// What a weapon's upgrades add up to at its current level. Sits on the weapon's pivot.
+#[derive(Component)]
+pub struct WeaponStats {
+	pub level: u32,
+	upgrades: Vec<WeaponUpgrade>,
+	pub damage: f32,
+	pub speed: f32,
+	pub sweep_size: f32,
+	pub bonus_charge: u32,
+}
+
+impl WeaponStats {
+	pub fn new(upgrades: Vec<WeaponUpgrade>, level: u32) -> Self {
+		let mut stats = Self {
+			level: 0,
+			upgrades,
+			damage: 1.0,
+			speed: 1.0,
+			sweep_size: 1.0,
+			bonus_charge: 0,
+		};
+		stats.set_level(level);
+		stats
+	}
+
+	pub fn set_level(&mut self, level: u32) {
+		self.level = level.min(self.upgrades.len() as u32);
+		self.damage = 1.0;
+		self.speed = 1.0;
+		self.sweep_size = 1.0;
+		self.bonus_charge = 0;
+
+		for upgrade in &self.upgrades[..self.level as usize] {
+			for modifier in &upgrade.modifiers {
+				match *modifier {
+					WeaponModifier::Damage(multiplier) => self.damage *= multiplier,
+					WeaponModifier::Speed(multiplier) => self.speed *= multiplier,
+					WeaponModifier::SweepSize(multiplier) => self.sweep_size *= multiplier,
+					WeaponModifier::MaxCharge(charge) => self.bonus_charge += charge,
+				}
+			}
+		}
+	}
+
+	/// The upgrade that would take this weapon to the next level, if it isn't maxed out.
+	pub fn next_upgrade(&self) -> Option<&WeaponUpgrade> {
+		self.upgrades.get(self.level as usize)
+	}
+
+	pub fn scale_sweep(&self, sweep: &SweepPivot) -> SweepPivot {
+		SweepPivot {
+			sweep_depth: sweep.sweep_depth * self.sweep_size,
+			sweep_height: sweep.sweep_height * self.sweep_size,
+			..sweep.clone()
+		}
+	}
+}
+
+/// Spends upgrade materials from the player's inventory to level up the weapon in hand.
+pub fn upgrade_active_weapon(
+	mut commands: Commands,
+	active_weapons: Query<&Weapon, With<ActiveWeapon>>,
+	mut weapons: Query<(&Weapon, &mut WeaponStats)>,
+	mut inventories: Query<&mut Inventory, With<PlayerBody>>,
+	upgrade_materials: Query<(), With<UpgradeMaterial>>,
+	mut save_data: ResMut<SaveData>,
+) {
+	let active_weapon = ok_or_return!(active_weapons.get_single());
+	let mut inventory = ok_or_return!(inventories.get_single_mut());
+	let (cost, level) = {
+		let (_, stats) = some_or_return!(weapons
+			.iter()
+			.find(|(weapon, _)| weapon.name == active_weapon.name));
+		let upgrade = some_or_return!(stats.next_upgrade());
+		(upgrade.materials as usize, stats.level + 1)
+	};
+
+	let materials: Vec<Entity> = inventory
+		.items()
+		.filter(|item| upgrade_materials.contains(*item))
+		.take(cost)
+		.collect();
+	if materials.len() < cost {
+		return;
+	}
+
+	for material in materials {
+		inventory.take(material);
+		commands.entity(material).despawn_recursive();
+	}
+
+	// Every copy of the weapon levels up together, since that's how it gets saved
+	for (weapon, mut stats) in weapons.iter_mut() {
+		if weapon.name == active_weapon.name {
+			stats.set_level(level);
+		}
+	}
+	save_data
+		.weapon_levels
+		.insert(active_weapon.name.clone(), level);
+}
@@ -11,13 +11,13 @@ use uuid::Uuid;
 
 use crate::entity::EntityKilled;
 use crate::input::button_just_pressed;
-use crate::inventory::{Inventory, Item};
+use crate::inventory::Inventory;
 use crate::iter_system::*;
 use crate::menus::*;
 use crate::npcs::Imp;
-use crate::player_controller::{interact_with, PlayerAction};
+use crate::player_controller::{interact_with, GameMode, PlayerAction, PlayerDied};
+use crate::some_or_return;
 use crate::util::map_event;
-use crate::{gridbox_material, some_or_return, BoxBundle};
 
 mod proposal;
 mod quest_markers;
@@ -83,8 +83,8 @@ impl Plugin for QuestingPlugin {
 						QuestEnded(prop.get(ev.quest_proposal).unwrap().quest_id)
 					}),
 					map_event(|In(ev): In<QuestCompleted>| QuestEnded(ev.0)),
-					spawn_quest_drops,
 					consume_quest_drop,
+					reset_quests_on_death,
 				),
 			);
 
@@ -257,6 +257,10 @@ impl InputManagerReference for QuestDeclined {
 #[derive(Event)]
 pub struct QuestEnded(pub QuestId);
 
+/// An item that can be handed in for a fetch quest.
+#[derive(Component)]
+pub struct QuestItem;
+
 #[derive(Event, Clone)]
 pub struct QuestCompleted(pub QuestId);
 
@@ -312,6 +316,7 @@ fn update_killed_imps(
 fn update_picked_up_items(
 	inventories: Query<&Inventory>,
 	changed_inventories: Query<&Inventory, Changed<Inventory>>,
+	quest_items: Query<(), With<QuestItem>>,
 	mut quests: ResMut<Quests>,
 ) {
 	let inventory = some_or_return!(if quests.is_changed() {
@@ -319,53 +324,10 @@ fn update_picked_up_items(
 	} else {
 		changed_inventories.iter().next()
 	});
-	let num_items = inventory.items.len();
+	let has_quest_item = inventory.items().any(|item| quest_items.contains(item));
 	for (_, quest) in quests.0.iter_mut() {
 		if let QuestType::Fetch { done } = &mut quest.quest_type {
-			*done = num_items > 0;
-		}
-	}
-}
-
-fn spawn_quest_drops(
-	mut ev_killed: EventReader<EntityKilled>,
-	mut commands: Commands,
-	quests: Res<Quests>,
-	imps: Query<&Transform, With<Imp>>,
-	items: Query<&Item>,
-	mut meshes: ResMut<Assets<Mesh>>,
-	mut materials: ResMut<Assets<StandardMaterial>>,
-	asset_server: Res<AssetServer>,
-) {
-	let num_fetch_quests = quests
-		.0
-		.values()
-		.filter(|quest| matches!(quest.quest_type, QuestType::Fetch { .. }))
-		.count();
-	let mut num_items = items.iter().count();
-
-	for EntityKilled { entity } in ev_killed.read() {
-		if num_items >= num_fetch_quests {
-			break;
-		}
-
-		if let Ok(transform) = imps.get(*entity) {
-			if rand::random() {
-				continue;
-			}
-
-			commands.spawn((
-				BoxBundle::new(
-					transform.translation + Vec3::Y * 0.2,
-					meshes.add(Cuboid::from_size(Vec3::splat(0.2))),
-					gridbox_material("orange", &mut materials, &asset_server),
-				)
-				.with_collider_size(0.1),
-				Item {
-					icon: asset_server.load("item.png"),
-				},
-			));
-			num_items += 1;
+			*done = has_quest_item;
 		}
 	}
 }
@@ -375,15 +337,37 @@ fn consume_quest_drop(
 	mut inventories: Query<&mut Inventory>,
 	mut commands: Commands,
 	quests: Res<Quests>,
+	quest_items: Query<(), With<QuestItem>>,
 ) {
 	for QuestCompleted(quest_id) in ev_completed.read() {
 		let quest = quests.0.get(quest_id).expect("Unknown quest");
 		if let QuestType::Fetch { .. } = &quest.quest_type {
 			if quest.quest_type.is_completed() {
 				let mut inventory = inventories.single_mut();
-				let item = inventory.items.pop().expect("No item to consume");
+				let item = inventory
+					.items()
+					.find(|item| quest_items.contains(*item))
+					.expect("No quest item to consume");
+				inventory.take(item);
 				commands.entity(item).despawn_recursive();
 			}
 		}
 	}
 }
+
+fn reset_quests_on_death(
+	mut ev_died: EventReader<PlayerDied>,
+	mut ev_ended: EventWriter<QuestEnded>,
+	quests: Res<Quests>,
+	game_mode: Res<GameMode>,
+) {
+	if !game_mode.death_penalties().reset_quests {
+		return;
+	}
+
+	for _ in ev_died.read() {
+		for quest_id in quests.0.keys() {
+			ev_ended.send(QuestEnded(*quest_id));
+		}
+	}
+}
@@ -0,0 +1,60 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::alchemy::AlchemyMachine;
+
+const SAVE_PATH: &str = "save.ron";
+
+pub struct SavePlugin;
+impl Plugin for SavePlugin {
+	fn build(&self, app: &mut App) {
+		app.insert_resource(SaveData::load()).add_systems(
+			Update,
+			write_save_data
+				.run_if(resource_changed::<SaveData>.and_then(not(resource_added::<SaveData>))),
+		);
+	}
+}
+
+/// Progress that carries over between sessions. Gets written out whenever it changes.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct SaveData {
+	/// How many upgrades each weapon has, by name
+	#[serde(default)]
+	pub weapon_levels: BTreeMap<String, u32>,
+	/// What's loaded into each alchemy machine, by name
+	#[serde(default)]
+	pub alchemy_machines: BTreeMap<String, AlchemyMachine>,
+}
+
+impl SaveData {
+	fn load() -> Self {
+		let Ok(contents) = std::fs::read_to_string(SAVE_PATH) else {
+			return Self::default();
+		};
+		ron::from_str(&contents).unwrap_or_else(|err| {
+			warn!("Could not read {SAVE_PATH}, starting fresh: {err}");
+			Self::default()
+		})
+	}
+
+	pub fn weapon_level(&self, name: &str) -> u32 {
+		self.weapon_levels.get(name).copied().unwrap_or_default()
+	}
+}
+
+fn write_save_data(save_data: Res<SaveData>) {
+	let contents = match ron::ser::to_string_pretty(&*save_data, PrettyConfig::default()) {
+		Ok(contents) => contents,
+		Err(err) => {
+			error!("Could not serialize save data: {err}");
+			return;
+		}
+	};
+	if let Err(err) = std::fs::write(SAVE_PATH, contents) {
+		error!("Could not write {SAVE_PATH}: {err}");
+	}
+}
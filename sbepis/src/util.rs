@@ -38,6 +38,19 @@ impl MapRange<Real> for Real {
 	}
 }
 
+/// Samples a piecewise linear curve through `(x, y)` points sorted by x, holding flat past either end.
+pub fn sample_curve(points: &[(f32, f32)], x: f32) -> Option<f32> {
+	match points.iter().position(|(point_x, _)| x < *point_x) {
+		None => points.last().map(|(_, y)| *y),
+		Some(0) => Some(points[0].1),
+		Some(index) => {
+			let (from_x, from_y) = points[index - 1];
+			let (to_x, to_y) = points[index];
+			Some(x.map_range(from_x..to_x, from_y..to_y))
+		}
+	}
+}
+
 pub trait TransformEx {
 	fn transform_vector3(&self, vector: Vec3) -> Vec3;
 	fn inverse_transform_point(&self, point: Vec3) -> Vec3;